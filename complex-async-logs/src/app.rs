@@ -1,61 +1,233 @@
-use std::{io::Stdout, time::Duration};
+use std::time::{Duration, Instant};
 
-use crossterm::event::{
-    Event as CrosstermEvent, EventStream as CrosstermEventStream, KeyCode, KeyEventKind,
+use ratatui::{CompletedFrame, Terminal};
+use tokio::{
+    sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender},
+    time::Interval,
 };
-use futures::{FutureExt, StreamExt};
-use ratatui::{prelude::CrosstermBackend, Terminal};
-use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+use tracing::{error, info, info_span, instrument, warn};
 
-use crate::{error::Error, Result, ScreenManager};
+#[cfg(unix)]
+use crate::capture::OutputCapture;
+use crate::{
+    backend::{self, Backend, EventSource, Input, KeyCode, TerminalWriter},
+    clipboard,
+    crash::CrashReporter,
+    debug_overlay::DebugOverlay,
+    error::Error,
+    screenshot, Action, Clipboard, Context, Logger, Notification, Notifier, Result, Scheduler,
+    ScreenManager, ScreenshotFormat, TimerHandle,
+};
+
+/// Toggles the debug overlay, handled before any screen or binding sees it
+pub(crate) const DEBUG_OVERLAY_KEY: KeyCode = KeyCode::F(12);
 
+#[derive(Debug, Clone)]
 pub enum Event {
     Key(KeyCode),
+    /// A key pressed by a script, the number of presses by scripts that led
+    /// to it
+    ScriptKey(KeyCode, u8),
     Tick,
     Frame,
+    Notify(Notification),
+    Timer(TimerHandle),
+    /// Changes how often frames are drawn and ticks are sent
+    SetRates(Rates),
+    /// Saves the screen to a file, confirmed with a toast
+    Screenshot(ScreenshotFormat),
+    /// Puts text on the clipboard, see [`Clipboard`]
+    Copy(String),
+    /// Text pasted into the terminal
+    Paste(String),
+    /// Runs a global action, as if its key was pressed
+    Action(Action),
     Quit,
 }
 
+impl Event {
+    /// Name of the variant, to trace events without their contents
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Event::Key(_) => "key",
+            Event::ScriptKey(..) => "script_key",
+            Event::Tick => "tick",
+            Event::Frame => "frame",
+            Event::Notify(_) => "notify",
+            Event::Timer(_) => "timer",
+            Event::SetRates(_) => "set_rates",
+            Event::Screenshot(_) => "screenshot",
+            Event::Copy(_) => "copy",
+            Event::Paste(_) => "paste",
+            Event::Action(_) => "action",
+            Event::Quit => "quit",
+        }
+    }
+}
+
+impl From<Input> for Event {
+    fn from(input: Input) -> Self {
+        match input {
+            Input::Key(key) => Event::Key(key),
+            Input::Paste(text) => Event::Paste(text),
+        }
+    }
+}
+
+/// Frames and ticks per second
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rates {
+    pub frame_rate: f64,
+    pub tick_rate: f64,
+}
+
+impl Rates {
+    fn is_valid(&self) -> bool {
+        [self.frame_rate, self.tick_rate]
+            .iter()
+            .all(|rate| rate.is_finite() && *rate > 0.0)
+    }
+}
+
 pub struct App {
     should_quit: bool,
-    crossterm_event: CrosstermEventStream,
-    frame_rate: f64,
-    tick_rate: f64,
+    input: EventSource,
+    rates: Rates,
     event_tx: UnboundedSender<Event>,
     event_rx: UnboundedReceiver<Event>,
-    terminal: Terminal<CrosstermBackend<Stdout>>,
+    terminal: Terminal<Backend>,
+    scheduler: Scheduler,
+    clipboard: Clipboard,
     screen_manager: ScreenManager,
+    debug_overlay: DebugOverlay,
+    crash_reporter: CrashReporter,
+    logger: Logger,
+    #[cfg(unix)]
+    output_capture: Option<OutputCapture>,
 }
 
 impl App {
-    pub async fn new(frame_rate: f64, tick_rate: f64) -> Result<Self> {
+    pub async fn new(frame_rate: f64, tick_rate: f64, logger: Logger) -> Result<Self> {
+        let rates = Rates {
+            frame_rate,
+            tick_rate,
+        };
         let (event_tx, event_rx) = unbounded_channel();
-        let screen_manager = ScreenManager::new(event_tx.clone()).await?;
-        let terminal = Terminal::new(CrosstermBackend::new(std::io::stdout()))?;
+        let crash_reporter = CrashReporter::install(
+            logger.handles.buffer.clone(),
+            Notifier::new(event_tx.clone()),
+        );
+        let scheduler = Scheduler::new(event_tx.clone());
+        let clipboard = Clipboard::new(event_tx.clone());
+        let screen_manager = ScreenManager::new(
+            event_tx.clone(),
+            scheduler.clone(),
+            clipboard.clone(),
+            logger.handles.clone(),
+            rates,
+        )
+        .await?;
+        let terminal = backend::terminal(TerminalWriter::Stdout(std::io::stdout()))
+            .context("creating the terminal")?;
+        let input = EventSource::new().context("reading terminal input")?;
 
         Ok(Self {
             should_quit: false,
-            crossterm_event: CrosstermEventStream::new(),
-            frame_rate,
-            tick_rate,
+            input,
+            rates,
             event_tx,
             event_rx,
+            scheduler,
+            clipboard,
             screen_manager,
+            debug_overlay: DebugOverlay::new(),
+            crash_reporter,
             terminal,
+            logger,
+            #[cfg(unix)]
+            output_capture: None,
         })
     }
 
+    /// Sends toast notifications from background tasks
+    pub fn notifier(&self) -> Notifier {
+        Notifier::new(self.event_tx.clone())
+    }
+
+    /// Current frame and tick rates, changed with [`Event::SetRates`]
+    pub fn rates(&self) -> Rates {
+        self.rates
+    }
+
+    /// Schedules events for the app, e.g. from background tasks
+    pub fn scheduler(&self) -> Scheduler {
+        self.scheduler.clone()
+    }
+
+    /// Copies to and pastes from the clipboard
+    pub fn clipboard(&self) -> Clipboard {
+        self.clipboard.clone()
+    }
+
+    /// Captures stdout and stderr into the log while [`App::run`] is drawing
+    ///
+    /// The terminal keeps drawing through a separate handle to the tty.
+    #[cfg(unix)]
+    pub fn capture_output(&mut self) -> Result<()> {
+        let capture = OutputCapture::new().context("duplicating stdout and stderr")?;
+        let tty = capture.tty().context("opening a handle to the terminal")?;
+        self.crash_reporter.set_tty(
+            tty.try_clone()
+                .context("opening a handle to the terminal")?,
+        );
+        let writer = TerminalWriter::Tty(std::io::BufWriter::new(tty));
+        self.terminal = backend::terminal(writer).context("creating the terminal")?;
+        self.output_capture = Some(capture);
+        Ok(())
+    }
+
+    /// Runs the UI until quit, restoring the terminal even if it fails
     pub async fn run(&mut self) -> Result<()> {
-        startup()?;
+        let mut result = backend::startup(&mut self.terminal).context("setting up the terminal");
+
+        #[cfg(unix)]
+        if let (Ok(()), Some(capture)) = (&result, &mut self.output_capture) {
+            result = capture.start().context("capturing stdout and stderr");
+        }
+
+        if result.is_ok() {
+            result = self.event_loop().await;
+        }
+
+        #[cfg(unix)]
+        if let Some(capture) = &mut self.output_capture {
+            let restored = capture.stop().context("restoring stdout and stderr");
+            result = result.and(restored);
+        }
+
+        result =
+            result.and(backend::shutdown(&mut self.terminal).context("restoring the terminal"));
 
-        //let mut terminal = Terminal::new(CrosstermBackend::new(std::io::stdout()))?;
+        if let Err(err) = &result {
+            error!("{}", err.report());
+        }
+        self.logger.flush();
+        result
+    }
 
-        let frame_rate = Duration::from_secs_f64(1.0 / self.frame_rate);
-        let tick_rate = Duration::from_secs_f64(1.0 / self.tick_rate);
-        let mut frame_interval = tokio::time::interval(frame_rate);
-        let mut tick_interval = tokio::time::interval(tick_rate);
+    async fn event_loop(&mut self) -> Result<()> {
+        let mut rates = self.rates;
+        let mut frame_interval = interval(rates.frame_rate);
+        let mut tick_interval = interval(rates.tick_rate);
 
         while !self.should_quit {
+            // Rates changed while handling the last event
+            if rates != self.rates {
+                rates = self.rates;
+                frame_interval = interval(rates.frame_rate);
+                tick_interval = interval(rates.tick_rate);
+            }
+
             tokio::select! {
                 _tick = tick_interval.tick() => {
                     self.event_tx.send(Event::Tick)?;
@@ -63,15 +235,11 @@ impl App {
                 _frame = frame_interval.tick() => {
                     self.event_tx.send(Event::Frame)?;
                 }
-                event = self.crossterm_event.next().fuse() => {
-                    match event.ok_or(Error::CrosstermEvent)?? {
-                        CrosstermEvent::Key(key) => {
-                            if let KeyEventKind::Press = key.kind {
-                                self.event_tx.send(Event::Key(key.code))?;
-                            }
-                        }
-                        _ => {}
-                    }
+                input = self.input.next() => {
+                    let input = input
+                        .ok_or(Error::Input)?
+                        .context("reading terminal input")?;
+                    self.event_tx.send(input.into())?;
                 }
                 Some(event) = self.event_rx.recv() => {
                     self.handle_event(event).await?;
@@ -80,32 +248,109 @@ impl App {
             }
         }
 
-        shutdown()
+        Ok(())
     }
 
+    // Skips the event itself, formatting it on every frame costs time and
+    // would put clipboard and paste text into logs and traces
+    #[instrument(skip_all, fields(kind = event.kind()))]
     async fn handle_event(&mut self, event: Event) -> Result<()> {
+        match &event {
+            Event::Tick => self.debug_overlay.record_tick(),
+            // Frames are counted once they have been drawn
+            Event::Frame => {}
+            event => self.debug_overlay.record_event(event.kind()),
+        }
+        self.crash_reporter.record_event(&event);
+
         match event {
+            Event::Key(DEBUG_OVERLAY_KEY) | Event::ScriptKey(DEBUG_OVERLAY_KEY, _) => {
+                self.debug_overlay.toggle()
+            }
             Event::Key(key) => self.screen_manager.handle_key(key).await?,
+            Event::ScriptKey(key, depth) => {
+                self.screen_manager.handle_script_key(key, depth).await?
+            }
+            Event::Tick => self.screen_manager.tick(),
+            Event::Notify(notification) => self.screen_manager.notify(notification),
+            Event::Timer(handle) => self.screen_manager.timer(handle),
             Event::Frame => {
-                self.terminal.draw(|frame| {
-                    self.screen_manager.draw(frame.area(), frame);
-                })?;
+                self.draw()?;
             }
+            Event::SetRates(rates) => self.set_rates(rates),
+            Event::Screenshot(format) => self.screenshot(format)?,
+            Event::Copy(text) => self.copy(&text)?,
+            Event::Paste(text) => self.screen_manager.paste(&text),
+            Event::Action(action) => self.screen_manager.run_action(action),
             Event::Quit => self.should_quit = true,
-            _ => {}
         }
         Ok(())
     }
-}
 
-fn startup() -> Result<()> {
-    crossterm::terminal::enable_raw_mode()?;
-    crossterm::execute!(std::io::stdout(), crossterm::terminal::EnterAlternateScreen)?;
-    Ok(())
+    fn draw(&mut self) -> Result<CompletedFrame<'_>> {
+        let _span = info_span!("terminal.draw").entered();
+        let queue_depth = self.event_rx.len();
+        let rates = self.rates;
+        let started = Instant::now();
+        let frame = self
+            .terminal
+            .draw(|frame| {
+                self.screen_manager.draw(frame.area(), frame);
+                self.debug_overlay.draw(
+                    frame.area(),
+                    frame,
+                    queue_depth,
+                    rates.frame_rate,
+                    rates.tick_rate,
+                );
+            })
+            .context("drawing a frame")?;
+        self.debug_overlay.record_frame(started.elapsed());
+        self.crash_reporter.record_size(frame.area);
+        Ok(frame)
+    }
+
+    /// Draws a frame and saves it, so the screenshot matches the screen even
+    /// if the popup asking for it was just closed
+    fn screenshot(&mut self, format: ScreenshotFormat) -> Result<()> {
+        let frame = self.draw()?;
+        let saved = screenshot::save(frame.buffer, format);
+
+        let notifier = self.notifier();
+        match saved {
+            Ok(path) => {
+                info!(path = %path.display(), ?format, "saved screenshot");
+                notifier.info(format!("Screenshot saved to {}", path.display()));
+            }
+            Err(err) => {
+                error!(%err, ?format, "saving a screenshot failed");
+                notifier.error(format!("Saving the screenshot failed: {err}"));
+            }
+        }
+        Ok(())
+    }
+
+    /// Sends `text` to the terminal's clipboard, and the local one if any
+    fn copy(&mut self, text: &str) -> Result<()> {
+        backend::write_escape(&mut self.terminal, &clipboard::osc52(text))
+            .context("copying through the terminal")?;
+        self.clipboard.set_local(text);
+        Ok(())
+    }
+
+    fn set_rates(&mut self, rates: Rates) {
+        if !rates.is_valid() {
+            warn!(?rates, "ignoring invalid frame or tick rate");
+            return;
+        }
+
+        info!(?rates, "changing frame and tick rates");
+        self.rates = rates;
+        self.screen_manager.set_rates(rates);
+    }
 }
 
-fn shutdown() -> Result<()> {
-    crossterm::terminal::disable_raw_mode()?;
-    crossterm::execute!(std::io::stdout(), crossterm::terminal::LeaveAlternateScreen)?;
-    Ok(())
+/// Interval firing `rate` times per second
+fn interval(rate: f64) -> Interval {
+    tokio::time::interval(Duration::from_secs_f64(1.0 / rate))
 }
@@ -0,0 +1,120 @@
+//! Terminal backend picked with the `crossterm` (default), `termion` or
+//! `termwiz` cargo feature
+//!
+//! Each backend module provides the same items: the ratatui `Backend` type,
+//! `terminal` to create it, `startup` and `shutdown` to switch the terminal
+//! in and out of raw mode, `write_escape` to send an escape sequence such as
+//! OSC 52 past ratatui, `restore_after_panic` for the crash reporter and an
+//! `EventSource` yielding [`Input`]. The rest of the app only uses these, so
+//! screens work the same on each.
+//! Only one may be enabled, so pick another one with e.g.
+//! `cargo run --no-default-features --features termion`.
+
+use std::io::{self, Stdout, Write};
+
+#[cfg(feature = "crossterm")]
+mod crossterm;
+#[cfg(feature = "crossterm")]
+pub use self::crossterm::{
+    restore_after_panic, shutdown, startup, terminal, write_escape, Backend, EventSource,
+};
+
+// The other backends step aside for the one before them, so that enabling
+// several only reports the error below
+#[cfg(all(feature = "termion", not(feature = "crossterm")))]
+mod termion;
+#[cfg(all(feature = "termion", not(feature = "crossterm")))]
+pub use self::termion::{
+    restore_after_panic, shutdown, startup, terminal, write_escape, Backend, EventSource,
+};
+
+#[cfg(all(
+    feature = "termwiz",
+    not(any(feature = "crossterm", feature = "termion"))
+))]
+mod termwiz;
+#[cfg(all(
+    feature = "termwiz",
+    not(any(feature = "crossterm", feature = "termion"))
+))]
+pub use self::termwiz::{
+    restore_after_panic, shutdown, startup, terminal, write_escape, Backend, EventSource,
+};
+
+#[cfg(not(any(feature = "crossterm", feature = "termion", feature = "termwiz")))]
+compile_error!("enable one of the `crossterm`, `termion` or `termwiz` features");
+
+#[cfg(any(
+    all(feature = "crossterm", feature = "termion"),
+    all(feature = "crossterm", feature = "termwiz"),
+    all(feature = "termion", feature = "termwiz"),
+))]
+compile_error!(
+    "enable only one of the `crossterm`, `termion` or `termwiz` features, \
+     crossterm is a default one so add `--no-default-features` to pick another"
+);
+
+/// Key press, independent of the backend
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyCode {
+    Char(char),
+    F(u8),
+    Enter,
+    Esc,
+    Tab,
+    BackTab,
+    Backspace,
+    Delete,
+    Insert,
+    Up,
+    Down,
+    Left,
+    Right,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+}
+
+/// Terminal input the app reacts to
+#[derive(Debug)]
+pub enum Input {
+    Key(KeyCode),
+    Paste(String),
+}
+
+/// Where the terminal draws, either stdout or a tty handle kept aside while
+/// stdout is captured
+pub enum TerminalWriter {
+    Stdout(Stdout),
+    #[cfg(unix)]
+    Tty(io::BufWriter<std::fs::File>),
+}
+
+impl Write for TerminalWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            TerminalWriter::Stdout(stdout) => stdout.write(buf),
+            #[cfg(unix)]
+            TerminalWriter::Tty(tty) => tty.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            TerminalWriter::Stdout(stdout) => stdout.flush(),
+            #[cfg(unix)]
+            TerminalWriter::Tty(tty) => tty.flush(),
+        }
+    }
+}
+
+#[cfg(unix)]
+impl std::os::fd::AsFd for TerminalWriter {
+    fn as_fd(&self) -> std::os::fd::BorrowedFd<'_> {
+        match self {
+            TerminalWriter::Stdout(stdout) => stdout.as_fd(),
+            TerminalWriter::Tty(tty) => tty.get_ref().as_fd(),
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use std::io::{self, Write};
+
+use crossterm::event::{
+    DisableBracketedPaste, EnableBracketedPaste, Event, EventStream, KeyCode as CrosstermKeyCode,
+    KeyEventKind,
+};
+use futures::StreamExt;
+use ratatui::{prelude::CrosstermBackend, Terminal};
+
+use super::{Input, KeyCode, TerminalWriter};
+
+pub type Backend = CrosstermBackend<TerminalWriter>;
+
+pub fn terminal(writer: TerminalWriter) -> io::Result<Terminal<Backend>> {
+    Terminal::new(CrosstermBackend::new(writer))
+}
+
+pub fn startup(terminal: &mut Terminal<Backend>) -> io::Result<()> {
+    crossterm::terminal::enable_raw_mode()?;
+    crossterm::execute!(
+        terminal.backend_mut(),
+        crossterm::terminal::EnterAlternateScreen,
+        EnableBracketedPaste
+    )
+}
+
+pub fn shutdown(terminal: &mut Terminal<Backend>) -> io::Result<()> {
+    crossterm::terminal::disable_raw_mode()?;
+    crossterm::execute!(
+        terminal.backend_mut(),
+        DisableBracketedPaste,
+        crossterm::terminal::LeaveAlternateScreen
+    )
+}
+
+pub fn write_escape(terminal: &mut Terminal<Backend>, sequence: &str) -> io::Result<()> {
+    let backend = terminal.backend_mut();
+    backend.write_all(sequence.as_bytes())?;
+    backend.flush()
+}
+
+/// Puts the terminal back from within the panic hook, where the app and its
+/// terminal can't be reached
+pub fn restore_after_panic(mut out: &mut dyn Write) {
+    let _ = crossterm::terminal::disable_raw_mode();
+    let _ = crossterm::execute!(
+        &mut out,
+        DisableBracketedPaste,
+        crossterm::terminal::LeaveAlternateScreen
+    );
+}
+
+pub struct EventSource {
+    events: EventStream,
+}
+
+impl EventSource {
+    pub fn new() -> io::Result<Self> {
+        Ok(Self {
+            events: EventStream::new(),
+        })
+    }
+
+    /// Waits for the next key press or paste, `None` once input is closed
+    pub async fn next(&mut self) -> Option<io::Result<Input>> {
+        loop {
+            let event = match self.events.next().await? {
+                Ok(event) => event,
+                Err(err) => return Some(Err(err)),
+            };
+            let input = match event {
+                Event::Key(key) if key.kind == KeyEventKind::Press => {
+                    convert_key(key.code).map(Input::Key)
+                }
+                Event::Paste(text) => Some(Input::Paste(text)),
+                _ => None,
+            };
+            if let Some(input) = input {
+                return Some(Ok(input));
+            }
+        }
+    }
+}
+
+fn convert_key(key: CrosstermKeyCode) -> Option<KeyCode> {
+    let key = match key {
+        CrosstermKeyCode::Char(c) => KeyCode::Char(c),
+        CrosstermKeyCode::F(n) => KeyCode::F(n),
+        CrosstermKeyCode::Enter => KeyCode::Enter,
+        CrosstermKeyCode::Esc => KeyCode::Esc,
+        CrosstermKeyCode::Tab => KeyCode::Tab,
+        CrosstermKeyCode::BackTab => KeyCode::BackTab,
+        CrosstermKeyCode::Backspace => KeyCode::Backspace,
+        CrosstermKeyCode::Delete => KeyCode::Delete,
+        CrosstermKeyCode::Insert => KeyCode::Insert,
+        CrosstermKeyCode::Up => KeyCode::Up,
+        CrosstermKeyCode::Down => KeyCode::Down,
+        CrosstermKeyCode::Left => KeyCode::Left,
+        CrosstermKeyCode::Right => KeyCode::Right,
+        CrosstermKeyCode::Home => KeyCode::Home,
+        CrosstermKeyCode::End => KeyCode::End,
+        CrosstermKeyCode::PageUp => KeyCode::PageUp,
+        CrosstermKeyCode::PageDown => KeyCode::PageDown,
+        _ => return None,
+    };
+    Some(key)
+}
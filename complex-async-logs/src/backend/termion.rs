@@ -0,0 +1,175 @@
+use std::{
+    io::{self, Write},
+    os::fd::AsFd,
+    thread,
+};
+
+use ratatui::{
+    backend::{ClearType, WindowSize},
+    buffer::Cell,
+    layout::{Position, Size},
+    prelude::TermionBackend,
+    Terminal,
+};
+use termion::{
+    event::Key,
+    input::TermRead,
+    raw::{IntoRawMode, RawTerminal},
+    screen::{ToAlternateScreen, ToMainScreen},
+};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver};
+
+use super::{Input, KeyCode, TerminalWriter};
+
+/// [`TermionBackend`] that asks the terminal it draws on for its size
+///
+/// termion asks stdout, which is a pipe once output is captured, so every
+/// draw would fail.
+pub struct Backend(TermionBackend<RawTerminal<TerminalWriter>>);
+
+impl ratatui::backend::Backend for Backend {
+    fn draw<'a, I>(&mut self, content: I) -> io::Result<()>
+    where
+        I: Iterator<Item = (u16, u16, &'a Cell)>,
+    {
+        self.0.draw(content)
+    }
+
+    fn append_lines(&mut self, n: u16) -> io::Result<()> {
+        self.0.append_lines(n)
+    }
+
+    fn hide_cursor(&mut self) -> io::Result<()> {
+        self.0.hide_cursor()
+    }
+
+    fn show_cursor(&mut self) -> io::Result<()> {
+        self.0.show_cursor()
+    }
+
+    fn get_cursor_position(&mut self) -> io::Result<Position> {
+        self.0.get_cursor_position()
+    }
+
+    fn set_cursor_position<P: Into<Position>>(&mut self, position: P) -> io::Result<()> {
+        self.0.set_cursor_position(position)
+    }
+
+    fn clear(&mut self) -> io::Result<()> {
+        self.0.clear()
+    }
+
+    fn clear_region(&mut self, clear_type: ClearType) -> io::Result<()> {
+        self.0.clear_region(clear_type)
+    }
+
+    fn size(&self) -> io::Result<Size> {
+        let (width, height) = termion::terminal_size_fd(&self.0.writer().as_fd())?;
+        Ok(Size::new(width, height))
+    }
+
+    fn window_size(&mut self) -> io::Result<WindowSize> {
+        let tty = self.0.writer().as_fd();
+        Ok(WindowSize {
+            columns_rows: termion::terminal_size_fd(&tty)?.into(),
+            pixels: termion::terminal_size_pixels_fd(&tty)?.into(),
+        })
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        ratatui::backend::Backend::flush(&mut self.0)
+    }
+}
+
+pub fn terminal(writer: TerminalWriter) -> io::Result<Terminal<Backend>> {
+    // termion only offers raw mode through the writer, which starts in it
+    let writer = writer.into_raw_mode()?;
+    writer.suspend_raw_mode()?;
+    Terminal::new(Backend(TermionBackend::new(writer)))
+}
+
+pub fn startup(terminal: &mut Terminal<Backend>) -> io::Result<()> {
+    let writer = terminal.backend_mut().0.writer_mut();
+    writer.activate_raw_mode()?;
+    write!(writer, "{ToAlternateScreen}")?;
+    writer.flush()
+}
+
+pub fn shutdown(terminal: &mut Terminal<Backend>) -> io::Result<()> {
+    let writer = terminal.backend_mut().0.writer_mut();
+    write!(writer, "{ToMainScreen}")?;
+    writer.flush()?;
+    writer.suspend_raw_mode()
+}
+
+pub fn write_escape(terminal: &mut Terminal<Backend>, sequence: &str) -> io::Result<()> {
+    let writer = terminal.backend_mut().0.writer_mut();
+    writer.write_all(sequence.as_bytes())?;
+    writer.flush()
+}
+
+/// Leaves the alternate screen from within the panic hook, raw mode is
+/// switched off when the terminal is dropped while unwinding
+pub fn restore_after_panic(out: &mut dyn Write) {
+    let _ = write!(out, "{ToMainScreen}\r");
+    let _ = out.flush();
+}
+
+/// Keys read from stdin on a separate thread, as termion only reads blocking
+pub struct EventSource {
+    keys: UnboundedReceiver<io::Result<KeyCode>>,
+}
+
+impl EventSource {
+    pub fn new() -> io::Result<Self> {
+        let (tx, keys) = unbounded_channel();
+        thread::Builder::new()
+            .name("termion-input".to_string())
+            .spawn(move || {
+                for key in io::stdin().keys() {
+                    let key = match key {
+                        Ok(key) => match convert_key(key) {
+                            Some(key) => Ok(key),
+                            None => continue,
+                        },
+                        Err(err) => Err(err),
+                    };
+                    if tx.send(key).is_err() {
+                        break;
+                    }
+                }
+            })?;
+        Ok(Self { keys })
+    }
+
+    /// Waits for the next key press, `None` once input is closed
+    ///
+    /// termion doesn't report pastes, they arrive as keys.
+    pub async fn next(&mut self) -> Option<io::Result<Input>> {
+        Some(self.keys.recv().await?.map(Input::Key))
+    }
+}
+
+fn convert_key(key: Key) -> Option<KeyCode> {
+    let key = match key {
+        Key::Char('\n') => KeyCode::Enter,
+        Key::Char('\t') => KeyCode::Tab,
+        Key::Char(c) => KeyCode::Char(c),
+        Key::F(n) => KeyCode::F(n),
+        Key::Esc => KeyCode::Esc,
+        Key::BackTab => KeyCode::BackTab,
+        Key::Backspace => KeyCode::Backspace,
+        Key::Delete => KeyCode::Delete,
+        Key::Insert => KeyCode::Insert,
+        Key::Up => KeyCode::Up,
+        Key::Down => KeyCode::Down,
+        Key::Left => KeyCode::Left,
+        Key::Right => KeyCode::Right,
+        Key::Home => KeyCode::Home,
+        Key::End => KeyCode::End,
+        Key::PageUp => KeyCode::PageUp,
+        Key::PageDown => KeyCode::PageDown,
+        _ => return None,
+    };
+    Some(key)
+}
@@ -0,0 +1,122 @@
+use std::{
+    io::{self, Write},
+    thread,
+};
+
+use ratatui::{prelude::TermwizBackend, Terminal};
+use termwiz::{
+    caps::Capabilities,
+    escape::csi::{DecPrivateMode, DecPrivateModeCode, Mode, CSI},
+    input::{InputEvent, KeyCode as TermwizKeyCode},
+    surface::{Change, CursorVisibility},
+    terminal::{buffered::BufferedTerminal, SystemTerminal, Terminal as _},
+};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver};
+
+use super::{Input, KeyCode, TerminalWriter};
+
+pub type Backend = TermwizBackend;
+
+/// Termwiz draws through its own copy of the stdout handle, taken here, so it
+/// keeps reaching the terminal once stdout is captured and `writer` is unused
+pub fn terminal(_writer: TerminalWriter) -> io::Result<Terminal<Backend>> {
+    // `TermwizBackend::new` would enter raw mode and the alternate screen
+    // right away, leave that to `startup`
+    let caps = Capabilities::new_from_env().map_err(io::Error::other)?;
+    let system = SystemTerminal::new_from_stdio(caps).map_err(io::Error::other)?;
+    let buffered = BufferedTerminal::new(system).map_err(io::Error::other)?;
+    Terminal::new(TermwizBackend::with_buffered_terminal(buffered))
+}
+
+pub fn startup(terminal: &mut Terminal<Backend>) -> io::Result<()> {
+    let system = terminal.backend_mut().buffered_terminal_mut().terminal();
+    system.set_raw_mode().map_err(io::Error::other)?;
+    system.enter_alternate_screen().map_err(io::Error::other)
+}
+
+pub fn shutdown(terminal: &mut Terminal<Backend>) -> io::Result<()> {
+    let buffered = terminal.backend_mut().buffered_terminal_mut();
+    buffered.add_change(Change::CursorVisibility(CursorVisibility::Visible));
+    buffered.flush().map_err(io::Error::other)?;
+    let system = buffered.terminal();
+    system.exit_alternate_screen().map_err(io::Error::other)?;
+    system.set_cooked_mode().map_err(io::Error::other)
+}
+
+pub fn write_escape(terminal: &mut Terminal<Backend>, sequence: &str) -> io::Result<()> {
+    let system = terminal.backend_mut().buffered_terminal_mut().terminal();
+    system
+        .render(&[Change::Text(sequence.to_string())])
+        .and_then(|()| system.flush())
+        .map_err(io::Error::other)
+}
+
+/// Leaves the alternate screen from within the panic hook so the crash
+/// notice stays visible, the rest is restored when the terminal is dropped
+/// while unwinding
+pub fn restore_after_panic(out: &mut dyn Write) {
+    let leave = CSI::Mode(Mode::ResetDecPrivateMode(DecPrivateMode::Code(
+        DecPrivateModeCode::ClearAndEnableAlternateScreen,
+    )));
+    let _ = write!(out, "{leave}\r");
+    let _ = out.flush();
+}
+
+/// Input polled on a separate thread from a second handle to the terminal, so
+/// the one drawing never blocks on input
+pub struct EventSource {
+    inputs: UnboundedReceiver<io::Result<Input>>,
+}
+
+impl EventSource {
+    pub fn new() -> io::Result<Self> {
+        let caps = Capabilities::new_from_env().map_err(io::Error::other)?;
+        let mut input = SystemTerminal::new_from_stdio(caps).map_err(io::Error::other)?;
+        let (tx, inputs) = unbounded_channel();
+        thread::Builder::new()
+            .name("termwiz-input".to_string())
+            .spawn(move || loop {
+                let input = match input.poll_input(None) {
+                    Ok(Some(InputEvent::Key(key))) => match convert_key(key.key) {
+                        Some(key) => Ok(Input::Key(key)),
+                        None => continue,
+                    },
+                    Ok(Some(InputEvent::Paste(text))) => Ok(Input::Paste(text)),
+                    Ok(_) => continue,
+                    Err(err) => Err(io::Error::other(err)),
+                };
+                if tx.send(input).is_err() {
+                    break;
+                }
+            })?;
+        Ok(Self { inputs })
+    }
+
+    /// Waits for the next key press or paste, `None` once input is closed
+    pub async fn next(&mut self) -> Option<io::Result<Input>> {
+        self.inputs.recv().await
+    }
+}
+
+fn convert_key(key: TermwizKeyCode) -> Option<KeyCode> {
+    let key = match key {
+        TermwizKeyCode::Char(c) => KeyCode::Char(c),
+        TermwizKeyCode::Function(n) => KeyCode::F(n),
+        TermwizKeyCode::Enter => KeyCode::Enter,
+        TermwizKeyCode::Escape => KeyCode::Esc,
+        TermwizKeyCode::Tab => KeyCode::Tab,
+        TermwizKeyCode::Backspace => KeyCode::Backspace,
+        TermwizKeyCode::Delete => KeyCode::Delete,
+        TermwizKeyCode::Insert => KeyCode::Insert,
+        TermwizKeyCode::UpArrow => KeyCode::Up,
+        TermwizKeyCode::DownArrow => KeyCode::Down,
+        TermwizKeyCode::LeftArrow => KeyCode::Left,
+        TermwizKeyCode::RightArrow => KeyCode::Right,
+        TermwizKeyCode::Home => KeyCode::Home,
+        TermwizKeyCode::End => KeyCode::End,
+        TermwizKeyCode::PageUp => KeyCode::PageUp,
+        TermwizKeyCode::PageDown => KeyCode::PageDown,
+        _ => return None,
+    };
+    Some(key)
+}
@@ -0,0 +1,122 @@
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, Write},
+    os::fd::{AsFd, AsRawFd, OwnedFd, RawFd},
+    thread,
+};
+
+use tracing::{info, warn};
+
+/// Redirects stdout and stderr into the log while the TUI is running
+///
+/// Anything printed with `println!`/`eprintln!` (by us or a dependency) is
+/// forwarded to `tracing` under the `stdout`/`stderr` targets, so it shows up
+/// in the log file and the log screen instead of drawing over the UI.
+pub struct OutputCapture {
+    saved_stdout: OwnedFd,
+    saved_stderr: OwnedFd,
+    active: bool,
+}
+
+impl OutputCapture {
+    pub fn new() -> io::Result<Self> {
+        Ok(Self {
+            saved_stdout: io::stdout().as_fd().try_clone_to_owned()?,
+            saved_stderr: io::stderr().as_fd().try_clone_to_owned()?,
+            active: false,
+        })
+    }
+
+    /// Handle to the real terminal that keeps working while output is captured
+    pub fn tty(&self) -> io::Result<File> {
+        Ok(File::from(self.saved_stdout.try_clone()?))
+    }
+
+    pub fn start(&mut self) -> io::Result<()> {
+        if self.active {
+            return Ok(());
+        }
+
+        io::stdout().flush()?;
+        io::stderr().flush()?;
+
+        redirect(libc::STDOUT_FILENO, Stream::Stdout)?;
+        if let Err(err) = redirect(libc::STDERR_FILENO, Stream::Stderr) {
+            // `stop` only undoes a complete start, so stdout is put back here
+            dup2(self.saved_stdout.as_raw_fd(), libc::STDOUT_FILENO)?;
+            return Err(err);
+        }
+        self.active = true;
+
+        Ok(())
+    }
+
+    /// Points stdout and stderr back at the terminal
+    pub fn stop(&mut self) -> io::Result<()> {
+        if !self.active {
+            return Ok(());
+        }
+
+        io::stdout().flush()?;
+        io::stderr().flush()?;
+
+        dup2(self.saved_stdout.as_raw_fd(), libc::STDOUT_FILENO)?;
+        dup2(self.saved_stderr.as_raw_fd(), libc::STDERR_FILENO)?;
+        self.active = false;
+
+        Ok(())
+    }
+}
+
+impl Drop for OutputCapture {
+    fn drop(&mut self) {
+        let _ = self.stop();
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Stream {
+    Stdout,
+    Stderr,
+}
+
+/// Replaces `fd` with the write end of a new pipe and forwards its lines to tracing
+///
+/// The reader thread exits once `fd` is restored and the pipe is closed. The
+/// pipe is close-on-exec, so only `fd` itself is inherited by child processes.
+fn redirect(fd: RawFd, stream: Stream) -> io::Result<()> {
+    let (read, write) = io::pipe()?;
+
+    // Started first, so `fd` is never left pointing at a pipe nobody reads
+    thread::Builder::new()
+        .name(format!("capture-fd{fd}"))
+        .spawn(move || forward_lines(read, stream))?;
+    dup2(write.as_raw_fd(), fd)
+}
+
+fn forward_lines(pipe: io::PipeReader, stream: Stream) {
+    let mut reader = BufReader::new(pipe);
+    let mut line = Vec::new();
+
+    while let Ok(len) = reader.read_until(b'\n', &mut line) {
+        if len == 0 {
+            break;
+        }
+
+        let text = String::from_utf8_lossy(&line);
+        let text = text.trim_end();
+        match stream {
+            Stream::Stdout => info!(target: "stdout", "{text}"),
+            Stream::Stderr => warn!(target: "stderr", "{text}"),
+        }
+        line.clear();
+    }
+}
+
+fn dup2(src: RawFd, dst: RawFd) -> io::Result<()> {
+    // SAFETY: dup2(2) only operates on descriptor numbers
+    if unsafe { libc::dup2(src, dst) } == -1 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
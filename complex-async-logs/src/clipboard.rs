@@ -0,0 +1,81 @@
+//! Copying text out of the app and reading what the user pastes
+//!
+//! Copies are written to the terminal as an OSC 52 sequence, which most
+//! terminals put on the system clipboard, also over SSH. With the
+//! `local-clipboard` feature the clipboard of the machine running the app is
+//! set too, and can be read back, whenever a display is available. Text
+//! pasted into the terminal arrives as [`Event::Paste`].
+
+#[cfg(feature = "local-clipboard")]
+use std::sync::{Arc, Mutex, PoisonError};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use tokio::sync::mpsc::UnboundedSender;
+#[cfg(feature = "local-clipboard")]
+use tracing::{debug, warn};
+
+use crate::Event;
+
+/// Copies to and pastes from the clipboard, from any screen or task
+#[derive(Clone)]
+pub struct Clipboard {
+    event_tx: UnboundedSender<Event>,
+    /// `None` when there is no local clipboard, e.g. over SSH
+    #[cfg(feature = "local-clipboard")]
+    local: Option<Arc<Mutex<arboard::Clipboard>>>,
+}
+
+impl Clipboard {
+    pub fn new(event_tx: UnboundedSender<Event>) -> Self {
+        Self {
+            event_tx,
+            #[cfg(feature = "local-clipboard")]
+            local: match arboard::Clipboard::new() {
+                Ok(local) => Some(Arc::new(Mutex::new(local))),
+                Err(err) => {
+                    debug!(%err, "no local clipboard, copying through the terminal only");
+                    None
+                }
+            },
+        }
+    }
+
+    /// Puts `text` on the clipboard, through the terminal and locally
+    pub fn copy(&self, text: impl Into<String>) {
+        // Only fails once the app is shutting down
+        let _ = self.event_tx.send(Event::Copy(text.into()));
+    }
+
+    /// Text on the local clipboard, `None` if there is none or it can't be
+    /// read
+    ///
+    /// Terminals don't share their clipboard with the app, what the user
+    /// pastes there arrives as [`Event::Paste`] instead.
+    pub fn paste(&self) -> Option<String> {
+        #[cfg(feature = "local-clipboard")]
+        if let Some(local) = &self.local {
+            let mut local = local.lock().unwrap_or_else(PoisonError::into_inner);
+            return local.get_text().ok();
+        }
+        None
+    }
+
+    /// Sets the local clipboard, the terminal is handled by the
+    /// [`App`](crate::App)
+    pub(crate) fn set_local(&self, text: &str) {
+        #[cfg(feature = "local-clipboard")]
+        if let Some(local) = &self.local {
+            let mut local = local.lock().unwrap_or_else(PoisonError::into_inner);
+            if let Err(err) = local.set_text(text) {
+                warn!(%err, "setting the local clipboard failed");
+            }
+        }
+        #[cfg(not(feature = "local-clipboard"))]
+        let _ = text;
+    }
+}
+
+/// OSC 52 sequence asking the terminal to put `text` on the clipboard
+pub fn osc52(text: &str) -> String {
+    format!("\x1b]52;c;{}\x07", STANDARD.encode(text))
+}
@@ -0,0 +1,184 @@
+use std::{
+    backtrace::Backtrace,
+    collections::VecDeque,
+    fmt::Write as _,
+    fs::{self, File},
+    io::{self, Write},
+    panic::{self, PanicHookInfo},
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc, Mutex, PoisonError,
+    },
+    thread::{self, ThreadId},
+};
+
+use ratatui::layout::Rect;
+use time::{macros::format_description, OffsetDateTime};
+
+use crate::{backend, paths, Event, LogBuffer, Notifier};
+
+/// Number of recent events included in a crash report
+const REPORT_EVENTS: usize = 20;
+/// Number of recent log lines included in a crash report
+const REPORT_LOG_LINES: usize = 50;
+
+/// Writes a crash report to the state dir when the app panics
+///
+/// The report holds the panic message, a backtrace, the app version, the
+/// terminal size, the last events handled and the last log lines. After the
+/// terminal is restored the user is told where to find it.
+///
+/// The hook sees panics of every thread, but only one on the UI thread ends
+/// the app. Tokio catches those of spawned tasks and the UI keeps running, so
+/// for them the terminal is left alone and a toast points at the report.
+#[derive(Clone)]
+pub struct CrashReporter {
+    ui_thread: ThreadId,
+    notifier: Notifier,
+    log_buffer: LogBuffer,
+    events: Arc<Mutex<VecDeque<String>>>,
+    tty: Arc<Mutex<Option<File>>>,
+    /// Size of the last frame drawn, width in the high half, as the hook
+    /// can't ask the terminal
+    size: Arc<AtomicU32>,
+}
+
+impl CrashReporter {
+    /// Creates the reporter and installs it as the panic hook, called from
+    /// the thread running the UI
+    pub fn install(log_buffer: LogBuffer, notifier: Notifier) -> Self {
+        let reporter = Self {
+            ui_thread: thread::current().id(),
+            notifier,
+            log_buffer,
+            events: Arc::new(Mutex::new(VecDeque::with_capacity(REPORT_EVENTS))),
+            tty: Arc::new(Mutex::new(None)),
+            size: Arc::new(AtomicU32::new(0)),
+        };
+
+        let hook_reporter = reporter.clone();
+        panic::set_hook(Box::new(move |info| hook_reporter.report(info)));
+
+        reporter
+    }
+
+    pub fn record_event(&self, event: &Event) {
+        if matches!(event, Event::Tick | Event::Frame) {
+            return;
+        }
+
+        let mut events = self.events.lock().unwrap_or_else(PoisonError::into_inner);
+        if events.len() == REPORT_EVENTS {
+            events.pop_front();
+        }
+        events.push_back(match event {
+            // Clipboard and pasted text may hold passwords, only the length is kept
+            Event::Copy(text) => format!("Copy({} bytes)", text.len()),
+            Event::Paste(text) => format!("Paste({} bytes)", text.len()),
+            event => format!("{event:?}"),
+        });
+    }
+
+    pub fn record_size(&self, area: Rect) {
+        let size = u32::from(area.width) << 16 | u32::from(area.height);
+        self.size.store(size, Ordering::Relaxed);
+    }
+
+    /// Sets where to print the crash notice while stdout is captured
+    pub fn set_tty(&self, tty: File) {
+        *self.tty.lock().unwrap_or_else(PoisonError::into_inner) = Some(tty);
+    }
+
+    /// Runs inside the panic hook, where locks may be held by the panicking
+    /// thread, so nothing here may log or block on a lock
+    fn report(&self, info: &PanicHookInfo<'_>) {
+        let report = self.build_report(info);
+        let saved = write_report(&report);
+
+        if thread::current().id() != self.ui_thread {
+            self.notifier.error(match saved {
+                Ok(path) => format!(
+                    "A background task panicked, a crash report was written to {}",
+                    path.display()
+                ),
+                Err(err) => format!("A background task panicked: {info}, no report: {err}"),
+            });
+            return;
+        }
+
+        let mut tty = self.tty.try_lock().ok();
+        let mut stdout = io::stdout();
+        let out: &mut dyn Write = match tty.as_deref_mut().and_then(Option::as_mut) {
+            Some(tty) => tty,
+            None => &mut stdout,
+        };
+
+        backend::restore_after_panic(out);
+
+        let _ = writeln!(out, "{info}");
+        let _ = match saved {
+            Ok(path) => writeln!(out, "A crash report was written to {}", path.display()),
+            Err(err) => writeln!(out, "Failed to write a crash report: {err}"),
+        };
+    }
+
+    fn build_report(&self, info: &PanicHookInfo<'_>) -> String {
+        let mut report = String::new();
+
+        let _ = writeln!(
+            report,
+            "{} {}",
+            env!("CARGO_PKG_NAME"),
+            env!("CARGO_PKG_VERSION")
+        );
+        let _ = writeln!(report, "time: {}", OffsetDateTime::now_utc());
+        match self.size.load(Ordering::Relaxed) {
+            0 => {
+                let _ = writeln!(report, "terminal: unknown, nothing was drawn yet");
+            }
+            size => {
+                let _ = writeln!(report, "terminal: {}x{}", size >> 16, size & 0xffff);
+            }
+        }
+
+        let _ = writeln!(report, "\n{info}");
+        let _ = writeln!(report, "\nbacktrace:\n{}", Backtrace::force_capture());
+
+        let _ = writeln!(report, "recent events:");
+        if let Ok(events) = self.events.try_lock() {
+            for event in events.iter() {
+                let _ = writeln!(report, "  {event}");
+            }
+        }
+
+        let _ = writeln!(report, "\nrecent logs:");
+        if let Some(records) = self.log_buffer.try_records() {
+            let skip = records.len().saturating_sub(REPORT_LOG_LINES);
+            for record in records.iter().skip(skip) {
+                let _ = writeln!(
+                    report,
+                    "  {} {:>5} {}: {}",
+                    record.time, record.level, record.target, record.message
+                );
+            }
+        }
+
+        report
+    }
+}
+
+fn write_report(report: &str) -> io::Result<PathBuf> {
+    let timestamp = OffsetDateTime::now_utc()
+        .format(format_description!(
+            "[year][month][day]-[hour][minute][second]"
+        ))
+        .map_err(io::Error::other)?;
+
+    let dir = paths::state_dir();
+    fs::create_dir_all(&dir)?;
+    let path = dir.join(format!("crash-{timestamp}.txt"));
+    fs::write(&path, report)?;
+
+    Ok(path)
+}
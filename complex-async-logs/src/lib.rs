@@ -1,10 +1,28 @@
 mod app;
+mod backend;
+#[cfg(unix)]
+mod capture;
+mod clipboard;
+mod crash;
+mod debug_overlay;
 mod error;
 mod logger;
+mod notification;
+mod paths;
+mod scheduler;
 mod screen_manager;
+mod screenshot;
+mod script;
+mod task;
 
-pub use app::{App, Event};
-pub use logger::logger_init;
-pub use screen_manager::ScreenManager;
+pub use app::{App, Event, Rates};
+pub use clipboard::Clipboard;
+pub use error::{Context, Error};
+pub use logger::{logger_init, LogBuffer, LogFilter, LogHandles, Logger};
+pub use notification::{Notification, NotificationLevel, Notifier};
+pub use scheduler::{Scheduler, TimerHandle, Timers};
+pub use screen_manager::{Action, ScreenManager, ScreenState};
+pub use screenshot::ScreenshotFormat;
+pub use task::spawn_named;
 
 pub type Result<T> = std::result::Result<T, error::Error>;
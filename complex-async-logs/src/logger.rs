@@ -1,15 +1,74 @@
 use time::macros::format_description;
-use tracing::level_filters::LevelFilter;
-use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_appender::non_blocking::{ErrorCounter, NonBlockingBuilder, WorkerGuard};
 use tracing_subscriber::{
     fmt::{self, time::UtcTime},
     prelude::*,
     Layer,
 };
 
-pub fn logger_init() {
+mod buffer;
+#[cfg(feature = "chrome-trace")]
+mod chrome;
+mod filter;
+mod rotate;
+
+pub use buffer::{BufferLayer, LogBuffer, LogRecord};
+pub use filter::LogFilter;
+pub use rotate::RotatingWriter;
+
+use crate::Result;
+
+/// Number of records kept in memory for the log screen
+const LOG_BUFFER_CAPACITY: usize = 2000;
+
+/// Number of lines queued for the writer thread before new lines are dropped
+const LOG_WRITER_LINES_LIMIT: usize = 128_000;
+
+/// Handles to the installed logger used by the UI
+///
+/// Dropping the logger flushes the file writer, which also happens while
+/// unwinding from a panic, so it must live as long as the app.
+pub struct Logger {
+    pub handles: LogHandles,
+    guard: Option<WorkerGuard>,
+    #[cfg(feature = "chrome-trace")]
+    chrome_guard: Option<tracing_chrome::FlushGuard>,
+}
+
+/// Cloneable parts of the [`Logger`] that screens need
+#[derive(Clone)]
+pub struct LogHandles {
+    pub buffer: LogBuffer,
+    pub filter: LogFilter,
+    pub dropped: ErrorCounter,
+}
+
+impl Logger {
+    /// Writes out all queued lines and stops the writer thread
+    ///
+    /// Anything logged afterwards is discarded.
+    pub fn flush(&mut self) {
+        self.guard.take();
+        #[cfg(feature = "chrome-trace")]
+        self.chrome_guard.take();
+    }
+}
+
+pub fn logger_init() -> Result<Logger> {
     // Setup log file
-    let file_appender = RollingFileAppender::new(Rotation::MINUTELY, "logs", "tiktok-tui.log");
+    let file_appender = RotatingWriter::new("logs", "tiktok-tui.log")?
+        .max_file_size(10 * 1024 * 1024)
+        .max_files(10)
+        .max_total_size(50 * 1024 * 1024)
+        .daily(true);
+
+    // Write on a background thread so slow disks don't stall the event loop
+    let (file_writer, guard) = NonBlockingBuilder::default()
+        .lossy(true)
+        .buffered_lines_limit(LOG_WRITER_LINES_LIMIT)
+        .thread_name("log-writer")
+        .finish(file_appender);
+    let dropped = file_writer.error_counter();
 
     // Create file layer
     let file_layer = fmt::Layer::new()
@@ -19,10 +78,44 @@ pub fn logger_init() {
         .with_timer(UtcTime::new(format_description!(
             "[year]-[month]-[day] [hour]:[minute]:[second]"
         )))
-        .with_writer(file_appender)
+        .with_writer(file_writer)
         .with_ansi(false);
 
+    // Keep recent records in memory for the log screen
+    let log_buffer = LogBuffer::new(LOG_BUFFER_CAPACITY);
+    let buffer_layer = BufferLayer::new(log_buffer.clone());
+
+    // Log filter that can be changed at runtime
+    let (filter, log_filter) = LogFilter::new();
+
+    // Per-frame spans for Perfetto, only with the `chrome-trace` feature
+    #[cfg(feature = "chrome-trace")]
+    let (chrome_layer, chrome_guard) = chrome::layer();
+    #[cfg(not(feature = "chrome-trace"))]
+    let chrome_layer = tracing_subscriber::layer::Identity::new();
+
+    // Task instrumentation for `tokio-console`, only with the `console` feature
+    #[cfg(feature = "console")]
+    let console_layer = console_subscriber::spawn();
+    #[cfg(not(feature = "console"))]
+    let console_layer = tracing_subscriber::layer::Identity::new();
+
+    // The log filter only applies to the file and the log screen, so the
+    // trace and console layers still see everything they need
     tracing_subscriber::registry()
-        .with(file_layer.with_filter(LevelFilter::DEBUG))
+        .with(file_layer.and_then(buffer_layer).with_filter(filter))
+        .with(chrome_layer)
+        .with(console_layer)
         .init();
+
+    Ok(Logger {
+        handles: LogHandles {
+            buffer: log_buffer,
+            filter: log_filter,
+            dropped,
+        },
+        guard: Some(guard),
+        #[cfg(feature = "chrome-trace")]
+        chrome_guard: Some(chrome_guard),
+    })
 }
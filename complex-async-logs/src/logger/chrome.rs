@@ -0,0 +1,24 @@
+use time::{macros::format_description, OffsetDateTime};
+use tracing::Subscriber;
+use tracing_chrome::{ChromeLayer, ChromeLayerBuilder, FlushGuard};
+use tracing_subscriber::registry::LookupSpan;
+
+/// Layer writing spans as Chrome trace-event JSON to `logs/trace-<time>.json`
+///
+/// The resulting file can be opened in Perfetto or `chrome://tracing`. The
+/// file is only complete once the returned guard is dropped.
+pub fn layer<S>() -> (ChromeLayer<S>, FlushGuard)
+where
+    S: Subscriber + for<'span> LookupSpan<'span> + Send + Sync,
+{
+    let timestamp = OffsetDateTime::now_utc()
+        .format(format_description!(
+            "[year][month][day]-[hour][minute][second]"
+        ))
+        .unwrap_or_default();
+
+    ChromeLayerBuilder::new()
+        .file(format!("logs/trace-{timestamp}.json"))
+        .include_args(true)
+        .build()
+}
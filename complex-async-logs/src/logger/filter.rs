@@ -0,0 +1,154 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use tracing::{level_filters::LevelFilter, warn};
+use tracing_subscriber::{reload, EnvFilter, Registry};
+
+use crate::{paths, Result};
+
+const DEFAULT_DIRECTIVES: &str = "debug";
+
+/// Runtime handle to the log filter
+///
+/// Changes are applied immediately and written to the state dir so the next
+/// launch starts with the same filter.
+#[derive(Clone)]
+pub struct LogFilter {
+    handle: reload::Handle<EnvFilter, Registry>,
+    directives: Arc<Mutex<String>>,
+    /// Where the directives are persisted
+    path: Arc<PathBuf>,
+}
+
+impl LogFilter {
+    /// Builds the reloadable filter from the persisted directives
+    pub fn new() -> (reload::Layer<EnvFilter, Registry>, Self) {
+        Self::load(filter_path())
+    }
+
+    fn load(path: PathBuf) -> (reload::Layer<EnvFilter, Registry>, Self) {
+        let directives = fs::read_to_string(&path)
+            .ok()
+            .map(|s| s.trim().to_string())
+            .filter(|s| EnvFilter::try_new(s).is_ok())
+            .unwrap_or_else(|| DEFAULT_DIRECTIVES.to_string());
+
+        let (layer, handle) = reload::Layer::new(EnvFilter::new(&directives));
+
+        let filter = Self {
+            handle,
+            directives: Arc::new(Mutex::new(directives)),
+            path: Arc::new(path),
+        };
+        (layer, filter)
+    }
+
+    /// Current directives, e.g. `info,my_crate::net=trace`
+    pub fn directives(&self) -> String {
+        self.directives
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Replaces the filter with `directives` and persists it
+    pub fn set_directives(&self, directives: &str) -> Result<()> {
+        let filter = EnvFilter::try_new(directives)?;
+        self.handle.reload(filter)?;
+
+        *self
+            .directives
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = directives.to_string();
+        if let Err(err) = persist(&self.path, directives) {
+            warn!("Failed to persist log filter: {err}");
+        }
+
+        Ok(())
+    }
+
+    /// Moves to the next more verbose level, wrapping from trace back to error
+    ///
+    /// Any per-target directives are dropped.
+    pub fn cycle_level(&self) -> Result<()> {
+        let current = self
+            .handle
+            .with_current(|filter| filter.max_level_hint())?
+            .unwrap_or(LevelFilter::DEBUG);
+
+        let next = match current {
+            LevelFilter::ERROR => LevelFilter::WARN,
+            LevelFilter::WARN => LevelFilter::INFO,
+            LevelFilter::INFO => LevelFilter::DEBUG,
+            LevelFilter::DEBUG => LevelFilter::TRACE,
+            _ => LevelFilter::ERROR,
+        };
+
+        self.set_directives(&next.to_string().to_lowercase())
+    }
+}
+
+fn filter_path() -> PathBuf {
+    paths::state_dir().join("log_filter")
+}
+
+fn persist(path: &Path, directives: &str) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, directives)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cycles_to_the_next_level() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("log_filter");
+        let (_layer, filter) = LogFilter::load(path.clone());
+        assert_eq!(filter.directives(), DEFAULT_DIRECTIVES);
+
+        for expected in ["trace", "error", "warn", "info", "debug"] {
+            filter.cycle_level().unwrap();
+            assert_eq!(filter.directives(), expected);
+            assert_eq!(fs::read_to_string(&path).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn cycling_drops_target_directives() {
+        let dir = tempfile::tempdir().unwrap();
+        let (_layer, filter) = LogFilter::load(dir.path().join("log_filter"));
+
+        filter.set_directives("warn,my_crate::net=info").unwrap();
+        filter.cycle_level().unwrap();
+
+        assert_eq!(filter.directives(), "debug");
+    }
+
+    #[test]
+    fn starts_with_the_persisted_directives() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("log_filter");
+
+        fs::write(&path, "warn\n").unwrap();
+        assert_eq!(LogFilter::load(path.clone()).1.directives(), "warn");
+
+        fs::write(&path, "not=a=filter").unwrap();
+        assert_eq!(LogFilter::load(path).1.directives(), DEFAULT_DIRECTIVES);
+    }
+
+    #[test]
+    fn rejects_invalid_directives() {
+        let dir = tempfile::tempdir().unwrap();
+        let (_layer, filter) = LogFilter::load(dir.path().join("log_filter"));
+
+        assert!(filter.set_directives("not=a=filter").is_err());
+        assert_eq!(filter.directives(), DEFAULT_DIRECTIVES);
+    }
+}
@@ -0,0 +1,364 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use flate2::{write::GzEncoder, Compression};
+use time::{macros::format_description, Date, OffsetDateTime};
+use tracing::warn;
+
+const DEFAULT_MAX_FILE_SIZE: u64 = 10 * 1024 * 1024;
+const DEFAULT_MAX_FILES: usize = 10;
+const DEFAULT_MAX_TOTAL_SIZE: u64 = 50 * 1024 * 1024;
+
+/// Log file writer that rotates by size, and optionally by day
+///
+/// When the active file would grow past `max_file_size`, or a line is written
+/// on a later (UTC) day than the previous one with `daily` set, it is renamed
+/// with a timestamp, gzipped, and the oldest archives are removed until at
+/// most `max_files` remain and they take no more than `max_total_size` bytes.
+///
+/// Failing to rotate is logged, and lines keep going to the current file.
+pub struct RotatingWriter {
+    dir: PathBuf,
+    file_name: String,
+    max_file_size: u64,
+    max_files: usize,
+    max_total_size: u64,
+    daily: bool,
+    file: File,
+    written: u64,
+    /// Day of the last write to the active file
+    day: Date,
+}
+
+impl RotatingWriter {
+    pub fn new(dir: impl AsRef<Path>, file_name: &str) -> io::Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+        let file = open_append(&dir.join(file_name))?;
+        let metadata = file.metadata()?;
+        let day = metadata.modified().map_or_else(
+            |_| today(),
+            |modified| OffsetDateTime::from(modified).date(),
+        );
+
+        Ok(Self {
+            dir,
+            file_name: file_name.to_string(),
+            max_file_size: DEFAULT_MAX_FILE_SIZE,
+            max_files: DEFAULT_MAX_FILES,
+            max_total_size: DEFAULT_MAX_TOTAL_SIZE,
+            daily: false,
+            file,
+            written: metadata.len(),
+            day,
+        })
+    }
+
+    pub fn max_file_size(mut self, bytes: u64) -> Self {
+        self.max_file_size = bytes;
+        self
+    }
+
+    pub fn max_files(mut self, count: usize) -> Self {
+        self.max_files = count;
+        self
+    }
+
+    pub fn max_total_size(mut self, bytes: u64) -> Self {
+        self.max_total_size = bytes;
+        self
+    }
+
+    /// Also starts a new file on the first write of each day
+    pub fn daily(mut self, daily: bool) -> Self {
+        self.daily = daily;
+        self
+    }
+
+    fn should_rotate(&self, len: usize, today: Date) -> bool {
+        self.written > 0
+            && (self.written + len as u64 > self.max_file_size || self.daily && today != self.day)
+    }
+
+    /// Moves the active file aside and opens a new one
+    ///
+    /// Only errors up to opening the new file are returned, archiving the old
+    /// one is best effort.
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+
+        let active = self.dir.join(&self.file_name);
+        let timestamp = OffsetDateTime::now_utc()
+            .format(format_description!(
+                "[year][month][day]-[hour][minute][second].[subsecond digits:3]"
+            ))
+            .map_err(io::Error::other)?;
+        let rotated = self.archive_path(&timestamp);
+        fs::rename(&active, &rotated)?;
+
+        self.file = open_append(&active)?;
+        self.written = 0;
+
+        // A failed compression leaves the plain file, which retention still covers
+        match compress(&rotated) {
+            Ok(()) => {
+                if let Err(err) = fs::remove_file(&rotated) {
+                    warn!(%err, path = %rotated.display(), "removing a compressed log file failed");
+                }
+            }
+            Err(err) => warn!(%err, path = %rotated.display(), "compressing a log file failed"),
+        }
+        if let Err(err) = self.enforce_retention() {
+            warn!(%err, "removing old log files failed");
+        }
+
+        Ok(())
+    }
+
+    /// `<file>.<timestamp>`, with a `_<n>` suffix when an archive from the same
+    /// millisecond exists
+    fn archive_path(&self, timestamp: &str) -> PathBuf {
+        let name = format!("{}.{timestamp}", self.file_name);
+        let mut path = self.dir.join(&name);
+        let mut n = 0;
+        while path.exists() || gz_path(&path).exists() {
+            n += 1;
+            path = self.dir.join(format!("{name}_{n}"));
+        }
+        path
+    }
+
+    /// Removes the oldest archives until both limits are satisfied
+    fn enforce_retention(&self) -> io::Result<()> {
+        let prefix = format!("{}.", self.file_name);
+        let mut archives = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if name.starts_with(&prefix) {
+                archives.push((name, entry.metadata()?.len()));
+            }
+        }
+
+        // Timestamps sort lexically, and a suffix sorts after its timestamp,
+        // so the oldest archives come first
+        archives.sort();
+
+        let mut count = archives.len();
+        let mut total: u64 = archives.iter().map(|(_, len)| len).sum();
+        for (name, len) in archives {
+            if count <= self.max_files && total <= self.max_total_size {
+                break;
+            }
+            fs::remove_file(self.dir.join(name))?;
+            count -= 1;
+            total -= len;
+        }
+
+        Ok(())
+    }
+}
+
+impl Write for RotatingWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let today = today();
+        if self.should_rotate(buf.len(), today) {
+            // The line still goes to the current file, which is retried once
+            // it has grown by another `max_file_size`
+            if let Err(err) = self.rotate() {
+                warn!(%err, "rotating the log file failed");
+                self.written = 0;
+            }
+        }
+        self.day = today;
+
+        let written = self.file.write(buf)?;
+        self.written += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+fn today() -> Date {
+    OffsetDateTime::now_utc().date()
+}
+
+fn open_append(path: &Path) -> io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+fn gz_path(path: &Path) -> PathBuf {
+    let mut gz_path = path.as_os_str().to_owned();
+    gz_path.push(".gz");
+    gz_path.into()
+}
+
+/// Writes `path` to `path.gz`
+fn compress(path: &Path) -> io::Result<()> {
+    let mut input = BufReader::new(File::open(path)?);
+    let output = BufWriter::new(File::create(gz_path(path))?);
+    let mut encoder = GzEncoder::new(output, Compression::default());
+    io::copy(&mut input, &mut encoder)?;
+    encoder.finish()?.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use flate2::read::GzDecoder;
+    use tempfile::TempDir;
+
+    use super::*;
+
+    const LINE: &[u8] = b"0123456789\n";
+
+    fn files(dir: &TempDir) -> Vec<String> {
+        let mut names: Vec<_> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        names
+    }
+
+    fn archives(dir: &TempDir) -> Vec<String> {
+        files(dir)
+            .into_iter()
+            .filter(|name| name.starts_with("app.log."))
+            .collect()
+    }
+
+    fn write_lines(writer: &mut RotatingWriter, count: usize) {
+        for _ in 0..count {
+            writer.write_all(LINE).unwrap();
+        }
+    }
+
+    #[test]
+    fn rotates_when_size_is_exceeded() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut writer = RotatingWriter::new(dir.path(), "app.log")
+            .unwrap()
+            .max_file_size(LINE.len() as u64 * 2);
+
+        write_lines(&mut writer, 2);
+        assert!(archives(&dir).is_empty());
+
+        write_lines(&mut writer, 1);
+        let archives = archives(&dir);
+        assert_eq!(archives.len(), 1);
+        assert!(archives[0].ends_with(".gz"));
+        assert_eq!(fs::read(dir.path().join("app.log")).unwrap(), LINE);
+    }
+
+    #[test]
+    fn keeps_archives_rotated_in_the_same_millisecond() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut writer = RotatingWriter::new(dir.path(), "app.log")
+            .unwrap()
+            .max_file_size(1);
+
+        write_lines(&mut writer, 4);
+        assert_eq!(archives(&dir).len(), 3);
+    }
+
+    #[test]
+    fn keeps_lines_longer_than_the_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut writer = RotatingWriter::new(dir.path(), "app.log")
+            .unwrap()
+            .max_file_size(1);
+
+        write_lines(&mut writer, 1);
+        assert!(archives(&dir).is_empty());
+        assert_eq!(fs::read(dir.path().join("app.log")).unwrap(), LINE);
+    }
+
+    #[test]
+    fn rotates_on_a_new_day() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut writer = RotatingWriter::new(dir.path(), "app.log")
+            .unwrap()
+            .daily(true);
+
+        write_lines(&mut writer, 2);
+        assert!(archives(&dir).is_empty());
+
+        writer.day = writer.day.previous_day().unwrap();
+        write_lines(&mut writer, 1);
+        assert_eq!(archives(&dir).len(), 1);
+    }
+
+    #[test]
+    fn ignores_the_day_unless_daily() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut writer = RotatingWriter::new(dir.path(), "app.log").unwrap();
+
+        write_lines(&mut writer, 1);
+        writer.day = writer.day.previous_day().unwrap();
+        write_lines(&mut writer, 1);
+        assert!(archives(&dir).is_empty());
+    }
+
+    #[test]
+    fn compresses_rotated_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut writer = RotatingWriter::new(dir.path(), "app.log")
+            .unwrap()
+            .max_file_size(1);
+
+        write_lines(&mut writer, 2);
+        let archives = archives(&dir);
+        assert_eq!(archives.len(), 1);
+
+        let mut decoder = GzDecoder::new(File::open(dir.path().join(&archives[0])).unwrap());
+        let mut content = Vec::new();
+        decoder.read_to_end(&mut content).unwrap();
+        assert_eq!(content, LINE);
+    }
+
+    #[test]
+    fn keeps_at_most_max_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut writer = RotatingWriter::new(dir.path(), "app.log")
+            .unwrap()
+            .max_file_size(1)
+            .max_files(2);
+
+        write_lines(&mut writer, 5);
+        assert_eq!(archives(&dir).len(), 2);
+    }
+
+    #[test]
+    fn keeps_at_most_max_total_size() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut writer = RotatingWriter::new(dir.path(), "app.log")
+            .unwrap()
+            .max_file_size(1)
+            .max_total_size(0);
+
+        write_lines(&mut writer, 3);
+        assert!(archives(&dir).is_empty());
+    }
+
+    #[test]
+    fn keeps_writing_when_retention_fails() {
+        let dir = tempfile::tempdir().unwrap();
+        // Sorts before the timestamped archives and can't be removed as a file
+        fs::create_dir(dir.path().join("app.log.0")).unwrap();
+        let mut writer = RotatingWriter::new(dir.path(), "app.log")
+            .unwrap()
+            .max_file_size(1)
+            .max_files(0);
+
+        write_lines(&mut writer, 2);
+        assert_eq!(fs::read(dir.path().join("app.log")).unwrap(), LINE);
+    }
+}
@@ -1,11 +1,48 @@
-use {{project-name}}::{logger_init, App, Result};
+use std::process::ExitCode;
+
+use clap::Parser;
+use {{project-name}}::{logger_init, App, Context, Result};
 use tracing::info;
 
+#[derive(Parser)]
+#[command(version, about, long_about = None)]
+struct Cli {
+    /// Send stray stdout and stderr output, e.g. from println! in a
+    /// dependency, to the log instead of the screen
+    #[cfg(unix)]
+    #[arg(long)]
+    capture_output: bool,
+}
+
 #[tokio::main]
-async fn main() -> Result<()> {
-    logger_init();
+async fn main() -> ExitCode {
+    match run().await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{}", err.report());
+            err.exit_code()
+        }
+    }
+}
+
+async fn run() -> Result<()> {
+    // Parsed on every platform for --help and --version, the only option
+    // is unix only
+    #[cfg_attr(not(unix), allow(unused_variables))]
+    let args = Cli::parse();
+    let logger = logger_init().context("setting up logging")?;
 
     info!("Starting application");
 
-    App::new(60f64, 10f64).await?.run().await
+    let mut app = App::new(60f64, 10f64, logger)
+        .await
+        .context("starting the app")?;
+
+    // Keep stray println!/eprintln! output from drawing over the UI
+    #[cfg(unix)]
+    if args.capture_output {
+        app.capture_output().context("capturing output")?;
+    }
+
+    app.run().await
 }
@@ -0,0 +1,51 @@
+use time::OffsetDateTime;
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::Event;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NotificationLevel {
+    Info,
+    Warn,
+    Error,
+}
+
+#[derive(Debug, Clone)]
+pub struct Notification {
+    pub level: NotificationLevel,
+    pub message: String,
+    pub time: OffsetDateTime,
+}
+
+/// Sends toast notifications to the app from screens or background tasks
+#[derive(Clone)]
+pub struct Notifier {
+    event_tx: UnboundedSender<Event>,
+}
+
+impl Notifier {
+    pub fn new(event_tx: UnboundedSender<Event>) -> Self {
+        Self { event_tx }
+    }
+
+    pub fn info(&self, message: impl Into<String>) {
+        self.notify(NotificationLevel::Info, message.into());
+    }
+
+    pub fn warn(&self, message: impl Into<String>) {
+        self.notify(NotificationLevel::Warn, message.into());
+    }
+
+    pub fn error(&self, message: impl Into<String>) {
+        self.notify(NotificationLevel::Error, message.into());
+    }
+
+    fn notify(&self, level: NotificationLevel, message: String) {
+        // Only fails once the app is shutting down, when nobody would see it
+        let _ = self.event_tx.send(Event::Notify(Notification {
+            level,
+            message,
+            time: OffsetDateTime::now_utc(),
+        }));
+    }
+}
@@ -0,0 +1,16 @@
+use std::path::PathBuf;
+
+/// Directory for state that should survive restarts, e.g. `~/.local/state/<app>`
+pub fn state_dir() -> PathBuf {
+    dirs::state_dir()
+        .or_else(dirs::data_local_dir)
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(env!("CARGO_PKG_NAME"))
+}
+
+/// Directory for files the user edits, e.g. `~/.config/<app>`
+pub fn config_dir() -> PathBuf {
+    dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(env!("CARGO_PKG_NAME"))
+}
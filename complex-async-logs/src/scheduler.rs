@@ -0,0 +1,254 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, MutexGuard, PoisonError,
+    },
+    time::Duration,
+};
+
+use tokio::{
+    sync::mpsc::UnboundedSender,
+    task::AbortHandle,
+    time::{Instant, MissedTickBehavior},
+};
+
+use crate::{spawn_named, Event, Result};
+
+/// Identifies a timer so it can be cancelled or matched in [`Event::Timer`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TimerHandle(u64);
+
+/// Runs one-shot and repeating timers that send events into the app's event
+/// channel
+///
+/// Owned by the [`App`](crate::App), cheap to clone. Screens should use
+/// [`Timers`] so their timers stop when the screen goes away.
+#[derive(Clone)]
+pub struct Scheduler {
+    event_tx: UnboundedSender<Event>,
+    timers: Arc<Mutex<HashMap<TimerHandle, AbortHandle>>>,
+    next_id: Arc<AtomicU64>,
+}
+
+impl Scheduler {
+    pub fn new(event_tx: UnboundedSender<Event>) -> Self {
+        Self {
+            event_tx,
+            timers: Arc::default(),
+            next_id: Arc::default(),
+        }
+    }
+
+    /// Sends `event` once after `delay`
+    pub fn once(&self, delay: Duration, event: Event) -> Result<TimerHandle> {
+        let handle = self.next_handle();
+        self.start(handle, delay, None, event)?;
+        Ok(handle)
+    }
+
+    /// Sends `event` every `period`, starting one period from now
+    pub fn every(&self, period: Duration, event: Event) -> Result<TimerHandle> {
+        let handle = self.next_handle();
+        self.start(handle, period, Some(period), event)?;
+        Ok(handle)
+    }
+
+    /// Stops a timer, returns `false` if it already fired or was cancelled
+    pub fn cancel(&self, handle: TimerHandle) -> bool {
+        match self.lock().remove(&handle) {
+            Some(task) => {
+                let running = !task.is_finished();
+                task.abort();
+                running
+            }
+            None => false,
+        }
+    }
+
+    /// Timers that are cancelled together when the returned value is dropped
+    pub fn timers(&self) -> Timers {
+        Timers {
+            scheduler: self.clone(),
+            handles: Vec::new(),
+        }
+    }
+
+    fn next_handle(&self) -> TimerHandle {
+        TimerHandle(self.next_id.fetch_add(1, Ordering::Relaxed))
+    }
+
+    fn start(
+        &self,
+        handle: TimerHandle,
+        delay: Duration,
+        period: Option<Duration>,
+        event: Event,
+    ) -> Result<()> {
+        let event_tx = self.event_tx.clone();
+        let task = spawn_named("timer", async move {
+            tokio::time::sleep(delay).await;
+            if event_tx.send(event.clone()).is_err() {
+                return;
+            }
+
+            let Some(period) = period else {
+                return;
+            };
+            let mut interval = tokio::time::interval_at(Instant::now() + period, period);
+            interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            loop {
+                interval.tick().await;
+                if event_tx.send(event.clone()).is_err() {
+                    return;
+                }
+            }
+        })?;
+
+        let mut timers = self.lock();
+        // Forget one-shot timers that already fired
+        timers.retain(|_, task| !task.is_finished());
+        timers.insert(handle, task.abort_handle());
+        Ok(())
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<TimerHandle, AbortHandle>> {
+        self.timers.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Timers owned by a screen, delivered as [`Event::Timer`]
+///
+/// Everything still scheduled is cancelled on drop, so removing a screen
+/// also stops its timers.
+pub struct Timers {
+    scheduler: Scheduler,
+    handles: Vec<TimerHandle>,
+}
+
+impl Timers {
+    /// Sends [`Event::Timer`] with the returned handle once after `delay`
+    pub fn once(&mut self, delay: Duration) -> Result<TimerHandle> {
+        let handle = self.scheduler.next_handle();
+        self.scheduler
+            .start(handle, delay, None, Event::Timer(handle))?;
+        self.track(handle);
+        Ok(handle)
+    }
+
+    /// Sends [`Event::Timer`] with the returned handle every `period`
+    pub fn every(&mut self, period: Duration) -> Result<TimerHandle> {
+        let handle = self.scheduler.next_handle();
+        self.scheduler
+            .start(handle, period, Some(period), Event::Timer(handle))?;
+        self.track(handle);
+        Ok(handle)
+    }
+
+    pub fn cancel(&mut self, handle: TimerHandle) -> bool {
+        self.handles.retain(|&h| h != handle);
+        self.scheduler.cancel(handle)
+    }
+
+    fn track(&mut self, handle: TimerHandle) {
+        let timers = self.scheduler.lock();
+        self.handles.retain(|h| timers.contains_key(h));
+        self.handles.push(handle);
+    }
+}
+
+impl Drop for Timers {
+    fn drop(&mut self) {
+        for handle in self.handles.drain(..) {
+            self.scheduler.cancel(handle);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver};
+
+    use super::*;
+
+    const PERIOD: Duration = Duration::from_millis(100);
+
+    fn scheduler() -> (Scheduler, UnboundedReceiver<Event>) {
+        let (event_tx, event_rx) = unbounded_channel();
+        (Scheduler::new(event_tx), event_rx)
+    }
+
+    /// Lets the timers run for `duration` of paused time
+    async fn wait(duration: Duration) {
+        tokio::time::sleep(duration).await;
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn once_fires_after_the_delay() {
+        let (scheduler, mut events) = scheduler();
+        let started = Instant::now();
+
+        scheduler.once(PERIOD, Event::Quit).unwrap();
+
+        assert!(matches!(events.recv().await, Some(Event::Quit)));
+        assert_eq!(started.elapsed(), PERIOD);
+        wait(PERIOD * 5).await;
+        assert!(events.try_recv().is_err());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn every_repeats_until_cancelled() {
+        let (scheduler, mut events) = scheduler();
+        let started = Instant::now();
+
+        let handle = scheduler.every(PERIOD, Event::Tick).unwrap();
+        for n in 1..=3 {
+            assert!(matches!(events.recv().await, Some(Event::Tick)));
+            assert_eq!(started.elapsed(), PERIOD * n);
+        }
+
+        assert!(scheduler.cancel(handle));
+        wait(PERIOD * 5).await;
+        assert!(events.try_recv().is_err());
+        assert!(!scheduler.cancel(handle));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn cancelled_before_firing() {
+        let (scheduler, mut events) = scheduler();
+
+        let handle = scheduler.once(PERIOD, Event::Quit).unwrap();
+        assert!(scheduler.cancel(handle));
+
+        wait(PERIOD * 5).await;
+        assert!(events.try_recv().is_err());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn screen_timers_send_their_handle() {
+        let (scheduler, mut events) = scheduler();
+        let mut timers = scheduler.timers();
+
+        let handle = timers.once(PERIOD).unwrap();
+
+        match events.recv().await {
+            Some(Event::Timer(fired)) => assert_eq!(fired, handle),
+            event => panic!("expected a timer, got {event:?}"),
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn screen_timers_stop_when_the_screen_goes_away() {
+        let (scheduler, mut events) = scheduler();
+        let mut timers = scheduler.timers();
+        let repeating = timers.every(PERIOD).unwrap();
+        timers.once(PERIOD * 3).unwrap();
+
+        assert!(matches!(events.recv().await, Some(Event::Timer(h)) if h == repeating));
+        drop(timers);
+
+        wait(PERIOD * 5).await;
+        assert!(events.try_recv().is_err());
+        assert!(scheduler.lock().is_empty());
+    }
+}
@@ -1,53 +1,437 @@
-use crossterm::event::KeyCode;
-use ratatui::{layout::Rect, Frame};
+use ratatui::{layout::Rect, widgets::Paragraph, Frame};
+use rhai::Map;
 use tokio::sync::mpsc::UnboundedSender;
+use tracing::{debug, error, info};
 
-use crate::{Event, Result};
+use crate::{
+    backend::KeyCode,
+    notification::Notification,
+    script::{self, Scripts},
+    Clipboard, Context, Event, LogHandles, Notifier, Rates, Result, Scheduler, TimerHandle,
+};
+use error_dialog::{DialogAction, ErrorDialog};
+use keymap::Keymap;
+use logs::Logs;
 use main::Main;
+use notifications::Notifications;
+use screenshot_picker::{PickerAction, ScreenshotPicker};
+use toasts::Toasts;
 
+mod error_dialog;
+mod keymap;
+mod logs;
 mod main;
+mod notifications;
+mod screenshot_picker;
+mod toasts;
+
+pub use keymap::{parse_key, Action};
 
 enum ScreenEvent {
     Quit,
+    Switch(ScreenState),
+    SetRates(Rates),
     None,
 }
 
-enum ScreenState {
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScreenState {
     Main,
+    Logs,
+    Notifications,
+}
+
+impl ScreenState {
+    /// Name used by scripts
+    pub fn name(self) -> &'static str {
+        match self {
+            ScreenState::Main => "main",
+            ScreenState::Logs => "logs",
+            ScreenState::Notifications => "notifications",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        [
+            ScreenState::Main,
+            ScreenState::Logs,
+            ScreenState::Notifications,
+        ]
+        .into_iter()
+        .find(|screen| screen.name() == name)
+    }
+}
+
+/// What to run again when the user picks retry in the error dialog
+#[derive(Debug, Clone, Copy)]
+enum Retry {
+    Key(ScreenState, KeyCode),
+    Init(ScreenState),
 }
 
 pub struct ScreenManager {
     screen_state: ScreenState,
     command_tx: UnboundedSender<Event>,
-    main: Main,
+    scheduler: Scheduler,
+    clipboard: Clipboard,
+    log_handles: LogHandles,
+    rates: Rates,
+    /// Screens are `None` when setting them up failed
+    main: Option<Main>,
+    logs: Option<Logs>,
+    notifications: Option<Notifications>,
+    toasts: Toasts,
+    keymap: Keymap,
+    /// `None` without an init script or when it failed to load
+    scripts: Option<Scripts>,
+    /// Presses by scripts that led to the key being handled, 0 for the user's
+    press_depth: u8,
+    /// Open while choosing a format for a screenshot
+    screenshot_picker: Option<ScreenshotPicker>,
+    /// Recoverable errors waiting to be dismissed, newest last
+    errors: Vec<ErrorDialog>,
 }
 
 /// Handle App key and UI
 impl ScreenManager {
-    pub async fn new(command_tx: UnboundedSender<Event>) -> Result<Self> {
-        Ok(Self {
+    pub async fn new(
+        command_tx: UnboundedSender<Event>,
+        scheduler: Scheduler,
+        clipboard: Clipboard,
+        log_handles: LogHandles,
+        rates: Rates,
+    ) -> Result<Self> {
+        let mut screen_manager = Self {
             screen_state: ScreenState::Main,
             command_tx,
-            main: Main::new().await?,
-        })
+            scheduler,
+            clipboard,
+            log_handles,
+            rates,
+            main: None,
+            logs: None,
+            notifications: None,
+            toasts: Toasts::new(rates.tick_rate),
+            keymap: Keymap::default(),
+            scripts: None,
+            press_depth: 0,
+            screenshot_picker: None,
+            errors: Vec::new(),
+        };
+
+        screen_manager.init(ScreenState::Main).await?;
+        screen_manager.init(ScreenState::Logs).await?;
+        screen_manager.init(ScreenState::Notifications).await?;
+        screen_manager.load_scripts();
+
+        Ok(screen_manager)
+    }
+
+    /// Loads and runs the init script, reporting failures with a toast
+    fn load_scripts(&mut self) {
+        let path = script::init_script();
+        if !path.exists() {
+            debug!(path = %path.display(), "no init script");
+            return;
+        }
+
+        let state = self.script_state();
+        let result = Scripts::load(&path, self.command_tx.clone()).and_then(|mut scripts| {
+            scripts.run(state)?;
+            Ok(scripts)
+        });
+        match result {
+            Ok(scripts) => {
+                info!(path = %path.display(), "loaded init script");
+                self.scripts = Some(scripts);
+                self.apply_bindings();
+            }
+            Err(err) => self.script_error(format!("{} not loaded: {err}", path.display())),
+        }
+    }
+
+    /// Adds the keys bound by the last script run to the keymap
+    fn apply_bindings(&mut self) {
+        let Some(scripts) = &self.scripts else {
+            return;
+        };
+        let mut missing = Vec::new();
+        for (key, function) in scripts.take_bindings() {
+            if scripts.has_function(&function) {
+                self.keymap.bind(key, Action::Script(function));
+            } else {
+                missing.push(function);
+            }
+        }
+        for function in missing {
+            self.script_error(format!("bind: no function {function}() in the script"));
+        }
+    }
+
+    fn call_script(&mut self, function: &str) {
+        let state = self.script_state();
+        let Some(scripts) = &mut self.scripts else {
+            return;
+        };
+        match scripts.call(function, state, self.press_depth) {
+            Ok(()) => self.apply_bindings(),
+            Err(err) => self.script_error(format!("{function}() failed: {err}")),
+        }
+    }
+
+    fn script_error(&self, message: String) {
+        error!("script: {message}");
+        Notifier::new(self.command_tx.clone()).error(message);
+    }
+
+    /// What scripts see from `state()`
+    fn script_state(&self) -> Map {
+        let mut state = Map::new();
+        state.insert("screen".into(), self.screen_state.name().into());
+        state.insert("frame_rate".into(), self.rates.frame_rate.into());
+        state.insert("tick_rate".into(), self.rates.tick_rate.into());
+        if let Some(main) = &self.main {
+            state.insert("main".into(), main.script_state().into());
+        }
+        if let Some(logs) = &self.logs {
+            state.insert("logs".into(), logs.script_state().into());
+        }
+        if let Some(notifications) = &self.notifications {
+            state.insert("notifications".into(), notifications.script_state().into());
+        }
+        state
+    }
+
+    /// Sets up a screen, showing an error dialog if that fails
+    async fn init(&mut self, screen_state: ScreenState) -> Result<()> {
+        let result = match screen_state {
+            ScreenState::Main => Main::new(self.rates)
+                .await
+                .map(|main| self.main = Some(main))
+                .context("setting up the main screen"),
+            ScreenState::Logs => Logs::new(
+                self.log_handles.clone(),
+                Notifier::new(self.command_tx.clone()),
+                self.scheduler.timers(),
+                self.clipboard.clone(),
+            )
+            .await
+            .map(|logs| self.logs = Some(logs))
+            .context("setting up the logs screen"),
+            ScreenState::Notifications => Notifications::new()
+                .await
+                .map(|notifications| self.notifications = Some(notifications))
+                .context("setting up the notification center"),
+        };
+
+        self.recover(result, Retry::Init(screen_state))?;
+        Ok(())
+    }
+
+    /// Handles a key pressed by a script, keeping track of how deep script
+    /// functions pressing keys go
+    pub async fn handle_script_key(&mut self, keycode: KeyCode, depth: u8) -> Result<()> {
+        self.press_depth = depth;
+        let result = self.handle_key(keycode).await;
+        self.press_depth = 0;
+        result
     }
 
     pub async fn handle_key(&mut self, keycode: KeyCode) -> Result<()> {
-        let screen_event = match self.screen_state {
-            ScreenState::Main => self.main.handle_key(keycode).await?,
+        if let Some(dialog) = self.errors.last_mut() {
+            match dialog.handle_key(keycode) {
+                DialogAction::Dismiss => {
+                    self.errors.pop();
+                }
+                DialogAction::Retry(retry) => {
+                    self.errors.pop();
+                    self.retry(retry).await?;
+                }
+                DialogAction::None => {}
+            }
+            return Ok(());
+        }
+
+        if let Some(picker) = &self.screenshot_picker {
+            match picker.handle_key(keycode) {
+                PickerAction::Close => self.screenshot_picker = None,
+                PickerAction::Take(format) => {
+                    self.screenshot_picker = None;
+                    self.command_tx.send(Event::Screenshot(format))?;
+                }
+                PickerAction::None => {}
+            }
+            return Ok(());
+        }
+
+        // Typing into a text field only leaves function keys to the keymap, so
+        // that bound characters can still be typed
+        let action = match keycode {
+            KeyCode::F(_) => self.keymap.get(keycode).cloned(),
+            _ if self.is_typing() => None,
+            _ => self.keymap.get(keycode).cloned(),
+        };
+        match action {
+            Some(action) => {
+                self.run_action(action);
+                Ok(())
+            }
+            None => self.screen_key(self.screen_state, keycode).await,
+        }
+    }
+
+    /// Whether the current screen takes typed text
+    fn is_typing(&self) -> bool {
+        match self.screen_state {
+            ScreenState::Logs => self.logs.as_ref().is_some_and(Logs::is_typing),
+            ScreenState::Main | ScreenState::Notifications => false,
+        }
+    }
+
+    /// Runs a global action, from the keymap or sent by a script
+    pub fn run_action(&mut self, action: Action) {
+        match action {
+            Action::Show(screen_state) => self.screen_state = screen_state,
+            Action::ToggleNotifications => {
+                self.screen_state = match self.screen_state {
+                    ScreenState::Notifications => ScreenState::Main,
+                    _ => ScreenState::Notifications,
+                };
+            }
+            Action::DismissToasts => self.toasts.dismiss_all(),
+            Action::PickScreenshot => self.screenshot_picker = Some(ScreenshotPicker),
+            Action::Script(function) => self.call_script(&function),
+        }
+    }
+
+    /// Counts down toasts, called on every [`Event::Tick`]
+    pub fn tick(&mut self) {
+        self.toasts.tick();
+    }
+
+    /// Lets screens and toasts follow frame and tick rate changes
+    pub fn set_rates(&mut self, rates: Rates) {
+        self.rates = rates;
+        self.toasts.set_tick_rate(rates.tick_rate);
+        if let Some(main) = &mut self.main {
+            main.set_rates(rates);
+        }
+    }
+
+    /// Passes a fired timer to the screens, only its owner reacts to it
+    pub fn timer(&mut self, handle: TimerHandle) {
+        if let Some(logs) = &mut self.logs {
+            logs.on_timer(handle);
+        }
+    }
+
+    /// Passes text pasted into the terminal to the current screen
+    pub fn paste(&mut self, text: &str) {
+        if !self.errors.is_empty() || self.screenshot_picker.is_some() {
+            return;
+        }
+        if let (ScreenState::Logs, Some(logs)) = (self.screen_state, &mut self.logs) {
+            logs.paste(text);
+        }
+    }
+
+    /// Shows a notification as a toast and keeps it in the notification center
+    pub fn notify(&mut self, notification: Notification) {
+        if let Some(notifications) = &mut self.notifications {
+            notifications.push(notification.clone());
+        }
+        self.toasts.push(notification);
+    }
+
+    async fn retry(&mut self, retry: Retry) -> Result<()> {
+        match retry {
+            Retry::Key(screen_state, keycode) => self.screen_key(screen_state, keycode).await,
+            Retry::Init(screen_state) => self.init(screen_state).await,
+        }
+    }
+
+    async fn screen_key(&mut self, screen_state: ScreenState, keycode: KeyCode) -> Result<()> {
+        let result = match screen_state {
+            ScreenState::Main => match &mut self.main {
+                Some(main) => main
+                    .handle_key(keycode)
+                    .await
+                    .with_context(|| format!("main screen: handling {keycode:?}")),
+                None => Ok(ScreenEvent::None),
+            },
+            ScreenState::Logs => match &mut self.logs {
+                Some(logs) => logs
+                    .handle_key(keycode)
+                    .await
+                    .with_context(|| format!("logs screen: handling {keycode:?}")),
+                None => Ok(ScreenEvent::None),
+            },
+            ScreenState::Notifications => match &mut self.notifications {
+                Some(notifications) => notifications
+                    .handle_key(keycode)
+                    .await
+                    .with_context(|| format!("notification center: handling {keycode:?}")),
+                None => Ok(ScreenEvent::None),
+            },
+        };
+
+        let Some(screen_event) = self.recover(result, Retry::Key(screen_state, keycode))? else {
+            return Ok(());
         };
 
         match screen_event {
             ScreenEvent::Quit => self.command_tx.send(Event::Quit)?,
+            ScreenEvent::Switch(screen_state) => self.screen_state = screen_state,
+            ScreenEvent::SetRates(rates) => self.command_tx.send(Event::SetRates(rates))?,
             ScreenEvent::None => {}
         }
 
         Ok(())
     }
 
+    /// Turns a recoverable error into an error dialog
+    ///
+    /// Fatal errors are passed on and end the app.
+    fn recover<T>(&mut self, result: Result<T>, retry: Retry) -> Result<Option<T>> {
+        match result {
+            Ok(value) => Ok(Some(value)),
+            Err(err) if err.is_fatal() => Err(err),
+            Err(err) => {
+                error!("{}", err.report());
+                self.errors.push(ErrorDialog::new(err, Some(retry)));
+                Ok(None)
+            }
+        }
+    }
+
     pub fn draw(&mut self, area: Rect, frame: &mut Frame<'_>) {
         match self.screen_state {
-            ScreenState::Main => self.main.draw(area, frame),
+            ScreenState::Main => match &self.main {
+                Some(main) => main.draw(area, frame),
+                None => draw_unavailable(area, frame),
+            },
+            ScreenState::Logs => match &mut self.logs {
+                Some(logs) => logs.draw(area, frame),
+                None => draw_unavailable(area, frame),
+            },
+            ScreenState::Notifications => match &self.notifications {
+                Some(notifications) => notifications.draw(area, frame),
+                None => draw_unavailable(area, frame),
+            },
+        }
+
+        self.toasts.draw(area, frame);
+
+        if let Some(picker) = &self.screenshot_picker {
+            picker.draw(area, frame);
+        }
+
+        if let Some(dialog) = self.errors.last() {
+            dialog.draw(area, frame);
         }
     }
 }
+
+fn draw_unavailable(area: Rect, frame: &mut Frame<'_>) {
+    frame.render_widget(Paragraph::new("This screen failed to load"), area);
+}
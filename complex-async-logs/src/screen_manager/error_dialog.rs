@@ -0,0 +1,88 @@
+use ratatui::{
+    layout::{Constraint, Flex, Layout, Rect},
+    style::{Color, Style},
+    text::Line,
+    widgets::{Block, Clear, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::{backend::KeyCode, Error};
+
+use super::Retry;
+
+pub enum DialogAction {
+    None,
+    Dismiss,
+    Retry(Retry),
+}
+
+/// Popup describing a recoverable error, optionally offering to retry
+pub struct ErrorDialog {
+    error: Error,
+    retry: Option<Retry>,
+    show_details: bool,
+}
+
+impl ErrorDialog {
+    pub fn new(error: Error, retry: Option<Retry>) -> Self {
+        Self {
+            error,
+            retry,
+            show_details: false,
+        }
+    }
+
+    pub fn handle_key(&mut self, key: KeyCode) -> DialogAction {
+        match key {
+            KeyCode::Esc | KeyCode::Enter => DialogAction::Dismiss,
+            KeyCode::Char('d') => {
+                self.show_details = !self.show_details;
+                DialogAction::None
+            }
+            KeyCode::Char('r') => match self.retry {
+                Some(retry) => DialogAction::Retry(retry),
+                None => DialogAction::None,
+            },
+            _ => DialogAction::None,
+        }
+    }
+
+    pub fn draw(&self, area: Rect, frame: &mut Frame<'_>) {
+        let mut lines = vec![Line::from(self.error.to_string())];
+        if self.show_details {
+            lines.push(Line::from(""));
+            lines.extend(
+                self.error
+                    .report()
+                    .lines()
+                    .map(|l| Line::from(l.to_string())),
+            );
+        }
+        lines.push(Line::from(""));
+
+        let mut help = String::from("Enter/Esc dismiss  d details");
+        if self.retry.is_some() {
+            help.push_str("  r retry");
+        }
+        lines.push(Line::styled(help, Style::default().fg(Color::DarkGray)));
+
+        let [area] = Layout::horizontal([Constraint::Percentage(60)])
+            .flex(Flex::Center)
+            .areas(area);
+        let [area] = Layout::vertical([Constraint::Length(lines.len() as u16 + 2)])
+            .flex(Flex::Center)
+            .areas(area);
+
+        let block = Block::bordered()
+            .title("Error")
+            .border_style(Style::default().fg(Color::Red));
+
+        frame.render_widget(Clear, area);
+        frame.render_widget(
+            Paragraph::new(lines)
+                .block(block)
+                .wrap(Wrap { trim: false }),
+            area,
+        );
+    }
+}
@@ -0,0 +1,78 @@
+use std::collections::HashMap;
+
+use crate::backend::KeyCode;
+
+use super::ScreenState;
+
+/// What a global key binding does
+#[derive(Debug, Clone, PartialEq)]
+pub enum Action {
+    Show(ScreenState),
+    ToggleNotifications,
+    DismissToasts,
+    PickScreenshot,
+    /// Calls a function of the user's script, see [`crate::script`]
+    Script(String),
+}
+
+/// Keys handled for every screen, before the current screen sees them
+///
+/// While a screen takes typed text only function keys are looked up here.
+pub struct Keymap {
+    bindings: HashMap<KeyCode, Action>,
+}
+
+impl Keymap {
+    pub fn get(&self, key: KeyCode) -> Option<&Action> {
+        self.bindings.get(&key)
+    }
+
+    /// Binds `key`, replacing what it did before
+    pub fn bind(&mut self, key: KeyCode, action: Action) {
+        self.bindings.insert(key, action);
+    }
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self {
+            bindings: HashMap::from([
+                (KeyCode::F(2), Action::ToggleNotifications),
+                (KeyCode::F(3), Action::DismissToasts),
+                (KeyCode::F(4), Action::PickScreenshot),
+            ]),
+        }
+    }
+}
+
+/// Parses a key name: a single character, `F1` to `F12`, or one of `Enter`,
+/// `Esc`, `Tab`, `Backspace`, `Delete`, `Insert`, `Up`, `Down`, `Left`,
+/// `Right`, `Home`, `End`, `PageUp` and `PageDown`
+pub fn parse_key(name: &str) -> Option<KeyCode> {
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Some(KeyCode::Char(c));
+    }
+
+    let key = match name {
+        "Enter" => KeyCode::Enter,
+        "Esc" => KeyCode::Esc,
+        "Tab" => KeyCode::Tab,
+        "Backspace" => KeyCode::Backspace,
+        "Delete" => KeyCode::Delete,
+        "Insert" => KeyCode::Insert,
+        "Up" => KeyCode::Up,
+        "Down" => KeyCode::Down,
+        "Left" => KeyCode::Left,
+        "Right" => KeyCode::Right,
+        "Home" => KeyCode::Home,
+        "End" => KeyCode::End,
+        "PageUp" => KeyCode::PageUp,
+        "PageDown" => KeyCode::PageDown,
+        _ => match name.strip_prefix('F')?.parse() {
+            Ok(n @ 1..=12) => KeyCode::F(n),
+            _ => return None,
+        },
+    };
+    Some(key)
+}
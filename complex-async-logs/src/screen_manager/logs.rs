@@ -0,0 +1,401 @@
+use std::time::Duration;
+
+use ratatui::{
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+use rhai::Map;
+use time::macros::format_description;
+use tracing::{instrument, Level};
+use tracing_appender::non_blocking::ErrorCounter;
+
+use crate::{
+    backend::KeyCode, logger::LogRecord, Clipboard, LogBuffer, LogFilter, LogHandles, Notifier,
+    Result, TimerHandle, Timers,
+};
+
+use super::{ScreenEvent, ScreenState};
+
+/// How often to check whether the log writer dropped lines
+const DROPPED_CHECK_PERIOD: Duration = Duration::from_secs(5);
+
+/// Which text field currently receives typed characters
+enum InputMode {
+    Normal,
+    Target,
+    Search,
+    Directives,
+}
+
+pub struct Logs {
+    buffer: LogBuffer,
+    filter: LogFilter,
+    dropped: ErrorCounter,
+    notifier: Notifier,
+    clipboard: Clipboard,
+    /// Cancelled when the screen is dropped
+    _timers: Timers,
+    dropped_check: TimerHandle,
+    /// Dropped line count at the last check
+    dropped_seen: usize,
+    level: Level,
+    target_filter: String,
+    search: String,
+    input_mode: InputMode,
+    /// Directives being typed for the global log filter
+    directives: String,
+    /// Index of the first visible record in the filtered list
+    top: usize,
+    follow: bool,
+    /// Height of the log body in the last draw, used for paging
+    page_height: usize,
+    /// Number of filtered records in the last draw
+    filtered_len: usize,
+}
+
+impl Logs {
+    pub async fn new(
+        handles: LogHandles,
+        notifier: Notifier,
+        mut timers: Timers,
+        clipboard: Clipboard,
+    ) -> Result<Self> {
+        let dropped_check = timers.every(DROPPED_CHECK_PERIOD)?;
+
+        Ok(Self {
+            buffer: handles.buffer,
+            filter: handles.filter,
+            dropped: handles.dropped,
+            notifier,
+            clipboard,
+            _timers: timers,
+            dropped_check,
+            dropped_seen: 0,
+            level: Level::DEBUG,
+            target_filter: String::new(),
+            search: String::new(),
+            input_mode: InputMode::Normal,
+            directives: String::new(),
+            top: 0,
+            follow: true,
+            page_height: 0,
+            filtered_len: 0,
+        })
+    }
+
+    /// Whether keys go to one of the text fields
+    pub fn is_typing(&self) -> bool {
+        !matches!(self.input_mode, InputMode::Normal)
+    }
+
+    #[instrument(name = "logs::handle_key", skip(self))]
+    pub async fn handle_key(&mut self, key: KeyCode) -> Result<ScreenEvent> {
+        match self.input_mode {
+            InputMode::Normal => Ok(self.handle_normal_key(key)),
+            InputMode::Target | InputMode::Search | InputMode::Directives => {
+                self.handle_input_key(key);
+                Ok(ScreenEvent::None)
+            }
+        }
+    }
+
+    fn handle_normal_key(&mut self, key: KeyCode) -> ScreenEvent {
+        match key {
+            KeyCode::Char('q') => return ScreenEvent::Quit,
+            KeyCode::Char('l') | KeyCode::Esc => return ScreenEvent::Switch(ScreenState::Main),
+            KeyCode::Char('1') => self.level = Level::ERROR,
+            KeyCode::Char('2') => self.level = Level::WARN,
+            KeyCode::Char('3') => self.level = Level::INFO,
+            KeyCode::Char('4') => self.level = Level::DEBUG,
+            KeyCode::Char('5') => self.level = Level::TRACE,
+            KeyCode::Char('t') => self.input_mode = InputMode::Target,
+            KeyCode::Char('/') => self.input_mode = InputMode::Search,
+            KeyCode::Char('v') => {
+                let result = self.filter.cycle_level();
+                self.set_filter_result(result);
+            }
+            KeyCode::Char('D') => {
+                self.directives = self.filter.directives();
+                self.input_mode = InputMode::Directives;
+            }
+            KeyCode::Char('y') => self.copy_page(),
+            KeyCode::Char('n') => self.jump_to_match(true),
+            KeyCode::Char('N') => self.jump_to_match(false),
+            KeyCode::Char('f') | KeyCode::End => self.follow = true,
+            KeyCode::Up | KeyCode::Char('k') => self.scroll_up(1),
+            KeyCode::Down | KeyCode::Char('j') => self.scroll_down(1),
+            KeyCode::PageUp => self.scroll_up(self.page_height),
+            KeyCode::PageDown => self.scroll_down(self.page_height),
+            KeyCode::Home | KeyCode::Char('g') => {
+                self.follow = false;
+                self.top = 0;
+            }
+            _ => {}
+        }
+        ScreenEvent::None
+    }
+
+    fn handle_input_key(&mut self, key: KeyCode) {
+        let input = match self.input_mode {
+            InputMode::Target => &mut self.target_filter,
+            InputMode::Search => &mut self.search,
+            InputMode::Directives => &mut self.directives,
+            InputMode::Normal => return,
+        };
+
+        match key {
+            KeyCode::Char(c) => input.push(c),
+            KeyCode::Insert => match self.clipboard.paste() {
+                Some(text) => input.push_str(first_line(&text)),
+                None => self
+                    .notifier
+                    .warn("Nothing to paste, use the terminal's paste"),
+            },
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Esc => {
+                input.clear();
+                self.input_mode = InputMode::Normal;
+            }
+            KeyCode::Enter => match self.input_mode {
+                InputMode::Directives => {
+                    self.input_mode = InputMode::Normal;
+                    let result = self.filter.set_directives(&self.directives);
+                    self.set_filter_result(result);
+                }
+                _ => {
+                    self.input_mode = InputMode::Normal;
+                    if !self.search.is_empty() {
+                        self.jump_to_match(true);
+                    }
+                }
+            },
+            _ => {}
+        }
+    }
+
+    /// What scripts see under `state().logs`, as of the last draw
+    pub fn script_state(&self) -> Map {
+        let mut state = Map::new();
+        state.insert("filter".into(), self.filter.directives().into());
+        state.insert("level".into(), self.level.to_string().into());
+        state.insert("target".into(), self.target_filter.clone().into());
+        state.insert("search".into(), self.search.clone().into());
+        state.insert("follow".into(), self.follow.into());
+        state.insert("records".into(), (self.filtered_len as i64).into());
+        state
+    }
+
+    /// Adds text pasted into the terminal to the field being typed in
+    pub fn paste(&mut self, text: &str) {
+        let input = match self.input_mode {
+            InputMode::Target => &mut self.target_filter,
+            InputMode::Search => &mut self.search,
+            InputMode::Directives => &mut self.directives,
+            InputMode::Normal => return,
+        };
+        input.push_str(first_line(text));
+    }
+
+    /// Copies the records on screen as plain text
+    fn copy_page(&self) {
+        let records = self.buffer.records();
+        let lines: Vec<String> = records
+            .iter()
+            .filter(|r| self.matches(r))
+            .skip(self.top)
+            .take(self.page_height)
+            .map(|record| self.record_line(record).to_string())
+            .collect();
+        drop(records);
+
+        if lines.is_empty() {
+            self.notifier.warn("No log lines to copy");
+            return;
+        }
+        self.notifier
+            .info(format!("Copied {} log lines", lines.len()));
+        self.clipboard.copy(lines.join("\n"));
+    }
+
+    /// Warns with a toast when the log writer started dropping lines
+    pub fn on_timer(&mut self, handle: TimerHandle) {
+        if handle != self.dropped_check {
+            return;
+        }
+
+        let dropped = self.dropped.dropped_lines();
+        if dropped > self.dropped_seen {
+            self.notifier.warn(format!(
+                "{} log lines dropped, the disk is not keeping up",
+                dropped - self.dropped_seen
+            ));
+            self.dropped_seen = dropped;
+        }
+    }
+
+    fn set_filter_result(&self, result: Result<()>) {
+        match result {
+            Ok(()) => self
+                .notifier
+                .info(format!("Log filter set to {}", self.filter.directives())),
+            Err(err) => self
+                .notifier
+                .error(format!("Log filter not changed: {err}")),
+        }
+    }
+
+    fn scroll_up(&mut self, lines: usize) {
+        self.follow = false;
+        self.top = self.top.saturating_sub(lines);
+    }
+
+    fn scroll_down(&mut self, lines: usize) {
+        self.top = self.top.saturating_add(lines);
+    }
+
+    /// Moves the view to the next (or previous) record containing the search term
+    fn jump_to_match(&mut self, forward: bool) {
+        if self.search.is_empty() {
+            return;
+        }
+
+        let records = self.buffer.records();
+        let filtered: Vec<&LogRecord> = records.iter().filter(|r| self.matches(r)).collect();
+        let found = if forward {
+            (self.top + 1..filtered.len()).find(|&i| self.is_search_hit(filtered[i]))
+        } else {
+            (0..self.top.min(filtered.len()))
+                .rev()
+                .find(|&i| self.is_search_hit(filtered[i]))
+        };
+        drop(records);
+
+        if let Some(index) = found {
+            self.follow = false;
+            self.top = index;
+        }
+    }
+
+    fn matches(&self, record: &LogRecord) -> bool {
+        record.level <= self.level
+            && (self.target_filter.is_empty() || record.target.contains(&self.target_filter))
+    }
+
+    fn is_search_hit(&self, record: &LogRecord) -> bool {
+        !self.search.is_empty() && record.message.contains(&self.search)
+    }
+
+    #[instrument(name = "logs::draw", skip_all)]
+    pub fn draw(&mut self, area: Rect, frame: &mut Frame<'_>) {
+        let [header_area, body_area, footer_area] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Fill(1),
+            Constraint::Length(1),
+        ])
+        .areas(area);
+
+        let block = Block::default().borders(Borders::TOP | Borders::BOTTOM);
+        let body_inner = block.inner(body_area);
+        self.page_height = body_inner.height as usize;
+
+        let records = self.buffer.records();
+        let filtered: Vec<&LogRecord> = records.iter().filter(|r| self.matches(r)).collect();
+        self.filtered_len = filtered.len();
+
+        let max_top = self.filtered_len.saturating_sub(self.page_height);
+        if self.follow || self.top > max_top {
+            self.top = max_top;
+        }
+
+        let lines: Vec<Line> = filtered
+            .iter()
+            .skip(self.top)
+            .take(self.page_height)
+            .map(|record| self.record_line(record))
+            .collect();
+        drop(records);
+
+        frame.render_widget(Paragraph::new(self.header_line()), header_area);
+        frame.render_widget(Paragraph::new(lines).block(block), body_area);
+        frame.render_widget(Paragraph::new(self.footer_line()), footer_area);
+    }
+
+    fn record_line(&self, record: &LogRecord) -> Line<'static> {
+        let time = record
+            .time
+            .format(format_description!("[hour]:[minute]:[second]"))
+            .unwrap_or_default();
+
+        let mut line = Line::from(vec![
+            Span::styled(time, Style::default().fg(Color::DarkGray)),
+            Span::raw(" "),
+            Span::styled(format!("{:>5}", record.level), level_style(record.level)),
+            Span::raw(" "),
+            Span::styled(record.target.clone(), Style::default().fg(Color::Cyan)),
+            Span::raw(": "),
+            Span::raw(record.message.clone()),
+        ]);
+
+        if self.is_search_hit(record) {
+            line = line.style(Style::default().bg(Color::Yellow).fg(Color::Black));
+        }
+
+        line
+    }
+
+    fn header_line(&self) -> Line<'static> {
+        let follow = if self.follow { "on" } else { "off" };
+        let mut spans = vec![
+            Span::styled("Logs", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(format!(
+                "  filter:{}  level<={}  target:{:?}  search:{:?}  follow:{}  {} records",
+                self.filter.directives(),
+                self.level,
+                self.target_filter,
+                self.search,
+                follow,
+                self.filtered_len
+            )),
+        ];
+        let dropped = self.dropped.dropped_lines();
+        if dropped > 0 {
+            spans.push(Span::styled(
+                format!("  {dropped} lines dropped"),
+                Style::default().fg(Color::Yellow),
+            ));
+        }
+        Line::from(spans)
+    }
+
+    fn footer_line(&self) -> Line<'static> {
+        match self.input_mode {
+            InputMode::Target => Line::from(format!("target> {}", self.target_filter)),
+            InputMode::Search => Line::from(format!("search> {}", self.search)),
+            InputMode::Directives => Line::from(format!("filter> {}", self.directives)),
+            InputMode::Normal => Line::from(
+                "1-5 level  t target  / search  n/N next/prev  f follow  ↑↓ PgUp PgDn scroll  y copy  v cycle filter  D edit filter  l back",
+            ),
+        }
+    }
+}
+
+/// Text fields hold one line, the rest of a multi-line paste is dropped
+fn first_line(text: &str) -> &str {
+    text.lines().next().unwrap_or_default()
+}
+
+fn level_style(level: Level) -> Style {
+    let color = match level {
+        Level::ERROR => Color::Red,
+        Level::WARN => Color::Yellow,
+        Level::INFO => Color::Green,
+        Level::DEBUG => Color::Blue,
+        Level::TRACE => Color::Magenta,
+    };
+    Style::default().fg(color)
+}
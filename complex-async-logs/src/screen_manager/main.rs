@@ -1,26 +1,70 @@
-use crossterm::event::KeyCode;
-use ratatui::{layout::Rect, widgets::Paragraph, Frame};
+use ratatui::{layout::Rect, text::Line, widgets::Paragraph, Frame};
 
-use crate::Result;
+use rhai::Map;
+use tracing::instrument;
 
-use super::ScreenEvent;
+use crate::{backend::KeyCode, Rates, Result};
 
-pub struct Main {}
+use super::{ScreenEvent, ScreenState};
+
+/// Rates used in low power mode
+const LOW_POWER_RATES: Rates = Rates {
+    frame_rate: 5.0,
+    tick_rate: 2.0,
+};
+
+pub struct Main {
+    rates: Rates,
+    /// Rates to go back to when leaving low power mode
+    normal_rates: Rates,
+}
 
 impl Main {
-    pub async fn new() -> Result<Self> {
-        Ok(Self {})
+    pub async fn new(rates: Rates) -> Result<Self> {
+        Ok(Self {
+            rates,
+            normal_rates: rates,
+        })
+    }
+
+    pub fn set_rates(&mut self, rates: Rates) {
+        self.rates = rates;
+    }
+
+    /// What scripts see under `state().main`
+    pub fn script_state(&self) -> Map {
+        let mut state = Map::new();
+        state.insert("low_power".into(), (self.rates == LOW_POWER_RATES).into());
+        state
     }
 
+    #[instrument(name = "main::handle_key", skip(self))]
     pub async fn handle_key(&mut self, key: KeyCode) -> Result<ScreenEvent> {
         match key {
             KeyCode::Char('q') => Ok(ScreenEvent::Quit),
+            KeyCode::Char('l') => Ok(ScreenEvent::Switch(ScreenState::Logs)),
+            KeyCode::Char('n') => Ok(ScreenEvent::Switch(ScreenState::Notifications)),
+            KeyCode::Char('p') => {
+                let rates = if self.rates == LOW_POWER_RATES {
+                    self.normal_rates
+                } else {
+                    LOW_POWER_RATES
+                };
+                Ok(ScreenEvent::SetRates(rates))
+            }
             _ => Ok(ScreenEvent::None),
         }
     }
 
+    #[instrument(name = "main::draw", skip_all)]
     pub fn draw(&self, area: Rect, frame: &mut Frame<'_>) {
-        let paragraph = Paragraph::new("Hi");
+        let paragraph = Paragraph::new(vec![
+            Line::from("Hi"),
+            Line::from(format!(
+                "{} fps, {} tps  (p: toggle low power)",
+                self.rates.frame_rate, self.rates.tick_rate
+            )),
+        ]);
         frame.render_widget(paragraph, area);
     }
 }
@@ -0,0 +1,148 @@
+use ratatui::{
+    layout::Rect,
+    style::Style,
+    text::{Line, Span},
+    widgets::{Block, Paragraph},
+    Frame,
+};
+use rhai::{Dynamic, Map};
+use time::macros::format_description;
+use tracing::instrument;
+
+use crate::{backend::KeyCode, notification::Notification, Result};
+
+use super::{toasts::level_title, ScreenEvent, ScreenState};
+
+/// Number of notifications kept in the history
+const HISTORY_CAPACITY: usize = 200;
+
+/// Notification center listing every notification, newest first
+pub struct Notifications {
+    history: Vec<Notification>,
+    scroll: usize,
+}
+
+impl Notifications {
+    pub async fn new() -> Result<Self> {
+        Ok(Self {
+            history: Vec::new(),
+            scroll: 0,
+        })
+    }
+
+    pub fn push(&mut self, notification: Notification) {
+        if self.history.len() == HISTORY_CAPACITY {
+            self.history.remove(0);
+        }
+        self.history.push(notification);
+    }
+
+    /// What scripts see under `state().notifications`
+    pub fn script_state(&self) -> Map {
+        let mut state = Map::new();
+        state.insert("count".into(), (self.history.len() as i64).into());
+        let latest = self.history.last().map_or(Dynamic::UNIT, |notification| {
+            notification.message.clone().into()
+        });
+        state.insert("latest".into(), latest);
+        state
+    }
+
+    #[instrument(name = "notifications::handle_key", skip(self))]
+    pub async fn handle_key(&mut self, key: KeyCode) -> Result<ScreenEvent> {
+        match key {
+            KeyCode::Char('q') => return Ok(ScreenEvent::Quit),
+            KeyCode::Char('n') | KeyCode::Esc => return Ok(ScreenEvent::Switch(ScreenState::Main)),
+            KeyCode::Char('c') => {
+                self.history.clear();
+                self.scroll = 0;
+            }
+            KeyCode::Up | KeyCode::Char('k') => self.scroll = self.scroll.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.scroll = (self.scroll + 1).min(self.history.len().saturating_sub(1));
+            }
+            _ => {}
+        }
+        Ok(ScreenEvent::None)
+    }
+
+    #[instrument(name = "notifications::draw", skip_all)]
+    pub fn draw(&self, area: Rect, frame: &mut Frame<'_>) {
+        let lines: Vec<Line> = self
+            .history
+            .iter()
+            .rev()
+            .skip(self.scroll)
+            .map(|notification| {
+                let (title, color) = level_title(notification.level);
+                let time = notification
+                    .time
+                    .format(format_description!("[hour]:[minute]:[second]"))
+                    .unwrap_or_default();
+                Line::from(vec![
+                    Span::raw(format!("{time} ")),
+                    Span::styled(format!("{title:<8}"), Style::default().fg(color)),
+                    Span::raw(notification.message.clone()),
+                ])
+            })
+            .collect();
+
+        let block = Block::bordered()
+            .title("Notifications")
+            .title_bottom("↑↓ scroll  c clear  n back");
+        frame.render_widget(Paragraph::new(lines).block(block), area);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time::OffsetDateTime;
+
+    use super::*;
+    use crate::NotificationLevel;
+
+    fn notification(message: &str) -> Notification {
+        Notification {
+            level: NotificationLevel::Warn,
+            message: message.to_string(),
+            time: OffsetDateTime::now_utc(),
+        }
+    }
+
+    #[tokio::test]
+    async fn keeps_a_limited_history() {
+        let mut notifications = Notifications::new().await.unwrap();
+        for n in 0..HISTORY_CAPACITY + 5 {
+            notifications.push(notification(&n.to_string()));
+        }
+
+        assert_eq!(notifications.history.len(), HISTORY_CAPACITY);
+        assert_eq!(notifications.history[0].message, "5");
+        let state = notifications.script_state();
+        assert_eq!(state["count"].as_int(), Ok(HISTORY_CAPACITY as i64));
+        assert_eq!(
+            state["latest"].clone().into_string(),
+            Ok((HISTORY_CAPACITY + 4).to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn scrolls_within_the_history_and_clears_it() {
+        let mut notifications = Notifications::new().await.unwrap();
+        for message in ["one", "two", "three"] {
+            notifications.push(notification(message));
+        }
+
+        for _ in 0..5 {
+            notifications.handle_key(KeyCode::Down).await.unwrap();
+        }
+        assert_eq!(notifications.scroll, 2);
+        notifications.handle_key(KeyCode::Up).await.unwrap();
+        assert_eq!(notifications.scroll, 1);
+
+        notifications.handle_key(KeyCode::Char('c')).await.unwrap();
+        assert!(notifications.history.is_empty());
+        assert_eq!(notifications.scroll, 0);
+        assert!(notifications.script_state()["latest"].is_unit());
+    }
+}
@@ -0,0 +1,65 @@
+use ratatui::{
+    layout::{Constraint, Flex, Layout, Rect},
+    style::{Color, Style},
+    text::Line,
+    widgets::{Block, Clear, Paragraph},
+    Frame,
+};
+
+use crate::{backend::KeyCode, ScreenshotFormat};
+
+const FORMATS: [(char, &str, ScreenshotFormat); 4] = [
+    ('t', "plain text", ScreenshotFormat::Text),
+    ('a', "ANSI colored text", ScreenshotFormat::Ansi),
+    ('h', "HTML page", ScreenshotFormat::Html),
+    ('s', "SVG image", ScreenshotFormat::Svg),
+];
+
+pub enum PickerAction {
+    None,
+    Close,
+    Take(ScreenshotFormat),
+}
+
+/// Popup asking which format to save a screenshot in
+pub struct ScreenshotPicker;
+
+impl ScreenshotPicker {
+    pub fn handle_key(&self, key: KeyCode) -> PickerAction {
+        match key {
+            KeyCode::Esc | KeyCode::F(4) => PickerAction::Close,
+            KeyCode::Char(c) => FORMATS
+                .iter()
+                .find(|(format_key, _, _)| *format_key == c)
+                .map_or(PickerAction::None, |(_, _, format)| {
+                    PickerAction::Take(*format)
+                }),
+            _ => PickerAction::None,
+        }
+    }
+
+    pub fn draw(&self, area: Rect, frame: &mut Frame<'_>) {
+        let mut lines: Vec<Line> = FORMATS
+            .iter()
+            .map(|(key, name, _)| Line::from(format!("{key}  {name}")))
+            .collect();
+        lines.push(Line::from(""));
+        lines.push(Line::styled(
+            "Esc cancel",
+            Style::default().fg(Color::DarkGray),
+        ));
+
+        let [area] = Layout::horizontal([Constraint::Length(30)])
+            .flex(Flex::Center)
+            .areas(area);
+        let [area] = Layout::vertical([Constraint::Length(lines.len() as u16 + 2)])
+            .flex(Flex::Center)
+            .areas(area);
+
+        frame.render_widget(Clear, area);
+        frame.render_widget(
+            Paragraph::new(lines).block(Block::bordered().title("Screenshot")),
+            area,
+        );
+    }
+}
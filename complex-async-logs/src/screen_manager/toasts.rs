@@ -0,0 +1,172 @@
+use std::collections::VecDeque;
+
+use ratatui::{
+    layout::Rect,
+    style::{Color, Style},
+    widgets::{Block, Clear, Paragraph},
+    Frame,
+};
+
+use crate::notification::{Notification, NotificationLevel};
+
+/// How long a toast stays on screen
+const TOAST_SECONDS: f64 = 4.0;
+/// Most toasts shown at once, older ones wait until these expire
+const MAX_VISIBLE: usize = 4;
+const TOAST_WIDTH: u16 = 40;
+const TOAST_HEIGHT: u16 = 3;
+
+struct Toast {
+    notification: Notification,
+    ticks_left: u32,
+}
+
+/// Stack of toasts in the bottom right corner, expired by [`Toasts::tick`]
+pub struct Toasts {
+    toasts: VecDeque<Toast>,
+    tick_rate: f64,
+}
+
+impl Toasts {
+    pub fn new(tick_rate: f64) -> Self {
+        Self {
+            toasts: VecDeque::new(),
+            tick_rate,
+        }
+    }
+
+    /// Rescales the remaining ticks so toasts keep their duration
+    pub fn set_tick_rate(&mut self, tick_rate: f64) {
+        let scale = tick_rate / self.tick_rate;
+        for toast in &mut self.toasts {
+            toast.ticks_left = (f64::from(toast.ticks_left) * scale).ceil().max(1.0) as u32;
+        }
+        self.tick_rate = tick_rate;
+    }
+
+    pub fn push(&mut self, notification: Notification) {
+        self.toasts.push_back(Toast {
+            notification,
+            ticks_left: (TOAST_SECONDS * self.tick_rate).ceil() as u32,
+        });
+    }
+
+    /// Counts down the visible toasts and drops the expired ones
+    pub fn tick(&mut self) {
+        for toast in self.toasts.iter_mut().take(MAX_VISIBLE) {
+            toast.ticks_left = toast.ticks_left.saturating_sub(1);
+        }
+        self.toasts.retain(|toast| toast.ticks_left > 0);
+    }
+
+    pub fn dismiss_all(&mut self) {
+        self.toasts.clear();
+    }
+
+    pub fn draw(&self, area: Rect, frame: &mut Frame<'_>) {
+        let width = TOAST_WIDTH.min(area.width);
+        let mut bottom = area.bottom();
+
+        for toast in self.toasts.iter().take(MAX_VISIBLE) {
+            if bottom < area.y + TOAST_HEIGHT {
+                break;
+            }
+            bottom -= TOAST_HEIGHT;
+            let toast_area = Rect::new(area.right() - width, bottom, width, TOAST_HEIGHT);
+
+            let (title, color) = level_title(toast.notification.level);
+            let block = Block::bordered()
+                .title(title)
+                .border_style(Style::default().fg(color));
+
+            frame.render_widget(Clear, toast_area);
+            frame.render_widget(
+                Paragraph::new(toast.notification.message.as_str()).block(block),
+                toast_area,
+            );
+        }
+    }
+}
+
+pub fn level_title(level: NotificationLevel) -> (&'static str, Color) {
+    match level {
+        NotificationLevel::Info => ("Info", Color::Green),
+        NotificationLevel::Warn => ("Warning", Color::Yellow),
+        NotificationLevel::Error => ("Error", Color::Red),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time::OffsetDateTime;
+
+    use super::*;
+
+    fn notification(message: &str) -> Notification {
+        Notification {
+            level: NotificationLevel::Info,
+            message: message.to_string(),
+            time: OffsetDateTime::now_utc(),
+        }
+    }
+
+    fn messages(toasts: &Toasts) -> Vec<&str> {
+        toasts
+            .toasts
+            .iter()
+            .map(|toast| toast.notification.message.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn expire_after_their_duration() {
+        let mut toasts = Toasts::new(10.0);
+        toasts.push(notification("saved"));
+
+        for _ in 0..39 {
+            toasts.tick();
+        }
+        assert_eq!(messages(&toasts), ["saved"]);
+
+        toasts.tick();
+        assert!(messages(&toasts).is_empty());
+    }
+
+    #[test]
+    fn waiting_toasts_start_counting_once_shown() {
+        let mut toasts = Toasts::new(10.0);
+        for message in ["1", "2", "3", "4", "5"] {
+            toasts.push(notification(message));
+        }
+
+        for _ in 0..40 {
+            toasts.tick();
+        }
+        assert_eq!(messages(&toasts), ["5"]);
+        assert_eq!(toasts.toasts[0].ticks_left, 40);
+    }
+
+    #[test]
+    fn keep_their_duration_when_the_tick_rate_changes() {
+        let mut toasts = Toasts::new(10.0);
+        toasts.push(notification("saved"));
+        for _ in 0..20 {
+            toasts.tick();
+        }
+
+        toasts.set_tick_rate(20.0);
+        assert_eq!(toasts.toasts[0].ticks_left, 40);
+        toasts.push(notification("later"));
+        assert_eq!(toasts.toasts[1].ticks_left, 80);
+    }
+
+    #[test]
+    fn dismissed_all_at_once() {
+        let mut toasts = Toasts::new(10.0);
+        toasts.push(notification("one"));
+        toasts.push(notification("two"));
+
+        toasts.dismiss_all();
+        assert!(messages(&toasts).is_empty());
+    }
+}
@@ -0,0 +1,504 @@
+//! Saving what is on screen to a file, for documentation and bug reports
+//!
+//! Takes a drawn ratatui [`Buffer`] and writes it as plain text, text with
+//! ANSI colors for `cat` or `less -R`, a standalone HTML page or an SVG image.
+
+use std::{fmt::Write, fs, io, path::PathBuf};
+
+use ratatui::{
+    buffer::Buffer,
+    style::{Color, Modifier},
+};
+use time::{macros::format_description, OffsetDateTime};
+use unicode_width::UnicodeWidthStr;
+
+use crate::paths;
+
+/// Colors used for [`Color::Reset`] in HTML and SVG
+const DEFAULT_FG: &str = "#e5e5e5";
+const DEFAULT_BG: &str = "#000000";
+const FONT_FAMILY: &str = "ui-monospace, Menlo, Consolas, monospace";
+/// SVG cell size in pixels for a 14px font
+const CELL_WIDTH: f64 = 8.4;
+const CELL_HEIGHT: f64 = 18.0;
+
+/// The 16 named colors, as xterm draws them
+const PALETTE: [&str; 16] = [
+    "#000000", "#cd0000", "#00cd00", "#cdcd00", "#0000ee", "#cd00cd", "#00cdcd", "#e5e5e5",
+    "#7f7f7f", "#ff0000", "#00ff00", "#ffff00", "#5c5cff", "#ff00ff", "#00ffff", "#ffffff",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScreenshotFormat {
+    Text,
+    Ansi,
+    Html,
+    Svg,
+}
+
+impl ScreenshotFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            ScreenshotFormat::Text => "txt",
+            ScreenshotFormat::Ansi => "ans",
+            ScreenshotFormat::Html => "html",
+            ScreenshotFormat::Svg => "svg",
+        }
+    }
+}
+
+/// Writes `buffer` to a new file in the `screenshots` state directory
+pub fn save(buffer: &Buffer, format: ScreenshotFormat) -> io::Result<PathBuf> {
+    let timestamp = OffsetDateTime::now_utc()
+        .format(format_description!(
+            "[year][month][day]-[hour][minute][second]-[subsecond digits:3]"
+        ))
+        .map_err(io::Error::other)?;
+
+    let dir = paths::state_dir().join("screenshots");
+    fs::create_dir_all(&dir)?;
+    let path = dir.join(format!("screenshot-{timestamp}.{}", format.extension()));
+    fs::write(&path, render(buffer, format))?;
+
+    Ok(path)
+}
+
+fn render(buffer: &Buffer, format: ScreenshotFormat) -> String {
+    let rows = rows(buffer);
+    match format {
+        ScreenshotFormat::Text => text(&rows),
+        ScreenshotFormat::Ansi => ansi(&rows),
+        ScreenshotFormat::Html => html(&rows),
+        ScreenshotFormat::Svg => svg(&rows, buffer.area.width, buffer.area.height),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct CellStyle {
+    fg: Color,
+    bg: Color,
+    modifier: Modifier,
+}
+
+impl CellStyle {
+    /// Foreground and background as CSS colors, after reversing and hiding
+    fn css_colors(&self) -> (String, String) {
+        let fg = css_color(self.fg).unwrap_or_else(|| DEFAULT_FG.to_string());
+        let bg = css_color(self.bg).unwrap_or_else(|| DEFAULT_BG.to_string());
+        let (fg, bg) = if self.modifier.contains(Modifier::REVERSED) {
+            (bg, fg)
+        } else {
+            (fg, bg)
+        };
+        if self.modifier.contains(Modifier::HIDDEN) {
+            (bg.clone(), bg)
+        } else {
+            (fg, bg)
+        }
+    }
+}
+
+/// Neighbouring cells of a row drawn in the same style
+struct Span {
+    /// Column of the first cell
+    x: u16,
+    /// Columns covered, wide characters take two
+    width: u16,
+    text: String,
+    style: CellStyle,
+}
+
+fn rows(buffer: &Buffer) -> Vec<Vec<Span>> {
+    let area = buffer.area;
+    (area.top()..area.bottom())
+        .map(|y| {
+            let mut spans: Vec<Span> = Vec::new();
+            // Cells hidden behind the previous wide character
+            let mut covered = 0;
+            for x in area.left()..area.right() {
+                if covered > 0 {
+                    covered -= 1;
+                    continue;
+                }
+                let cell = &buffer[(x, y)];
+                let symbol = cell.symbol();
+                let width = symbol.width().max(1) as u16;
+                covered = width - 1;
+
+                let style = CellStyle {
+                    fg: cell.fg,
+                    bg: cell.bg,
+                    modifier: cell.modifier,
+                };
+                match spans.last_mut() {
+                    Some(span) if span.style == style => {
+                        span.text.push_str(symbol);
+                        span.width += width;
+                    }
+                    _ => spans.push(Span {
+                        x: x - area.left(),
+                        width,
+                        text: symbol.to_string(),
+                        style,
+                    }),
+                }
+            }
+            spans
+        })
+        .collect()
+}
+
+fn text(rows: &[Vec<Span>]) -> String {
+    let mut out = String::new();
+    for row in rows {
+        let line: String = row.iter().map(|span| span.text.as_str()).collect();
+        out.push_str(line.trim_end());
+        out.push('\n');
+    }
+    out
+}
+
+fn ansi(rows: &[Vec<Span>]) -> String {
+    let mut out = String::new();
+    for row in rows {
+        for span in row {
+            let _ = write!(out, "\x1b[{}m{}", sgr(&span.style), span.text);
+        }
+        out.push_str("\x1b[0m\n");
+    }
+    out
+}
+
+/// SGR parameters setting exactly `style`
+fn sgr(style: &CellStyle) -> String {
+    let mut params = vec!["0".to_string()];
+    let modifiers = [
+        (Modifier::BOLD, "1"),
+        (Modifier::DIM, "2"),
+        (Modifier::ITALIC, "3"),
+        (Modifier::UNDERLINED, "4"),
+        (Modifier::SLOW_BLINK, "5"),
+        (Modifier::RAPID_BLINK, "6"),
+        (Modifier::REVERSED, "7"),
+        (Modifier::HIDDEN, "8"),
+        (Modifier::CROSSED_OUT, "9"),
+    ];
+    for (modifier, param) in modifiers {
+        if style.modifier.contains(modifier) {
+            params.push(param.to_string());
+        }
+    }
+    params.extend(ansi_color(style.fg, false));
+    params.extend(ansi_color(style.bg, true));
+    params.join(";")
+}
+
+fn ansi_color(color: Color, background: bool) -> Option<String> {
+    let offset = if background { 10 } else { 0 };
+    let param = match color {
+        Color::Reset => return None,
+        Color::Rgb(r, g, b) => format!("{};2;{r};{g};{b}", 38 + offset),
+        Color::Indexed(index) => format!("{};5;{index}", 38 + offset),
+        named => {
+            let index = named_index(named)?;
+            let base = if index < 8 {
+                30 + index
+            } else {
+                90 + index - 8
+            };
+            (base + offset).to_string()
+        }
+    };
+    Some(param)
+}
+
+/// Position of a named color in the 16 color palette
+fn named_index(color: Color) -> Option<u8> {
+    let index = match color {
+        Color::Black => 0,
+        Color::Red => 1,
+        Color::Green => 2,
+        Color::Yellow => 3,
+        Color::Blue => 4,
+        Color::Magenta => 5,
+        Color::Cyan => 6,
+        Color::Gray => 7,
+        Color::DarkGray => 8,
+        Color::LightRed => 9,
+        Color::LightGreen => 10,
+        Color::LightYellow => 11,
+        Color::LightBlue => 12,
+        Color::LightMagenta => 13,
+        Color::LightCyan => 14,
+        Color::White => 15,
+        _ => return None,
+    };
+    Some(index)
+}
+
+/// `color` as `#rrggbb`, `None` for [`Color::Reset`]
+fn css_color(color: Color) -> Option<String> {
+    let (r, g, b) = match color {
+        Color::Reset => return None,
+        Color::Rgb(r, g, b) => (r, g, b),
+        Color::Indexed(index) if index < 16 => return Some(PALETTE[usize::from(index)].into()),
+        Color::Indexed(index) if index < 232 => {
+            // 6x6x6 color cube
+            let level = |value: u8| if value == 0 { 0 } else { 55 + value * 40 };
+            let index = index - 16;
+            (level(index / 36), level(index / 6 % 6), level(index % 6))
+        }
+        Color::Indexed(index) => {
+            let gray = 8 + (index - 232) * 10;
+            (gray, gray, gray)
+        }
+        named => return named_index(named).map(|index| PALETTE[usize::from(index)].into()),
+    };
+    Some(format!("#{r:02x}{g:02x}{b:02x}"))
+}
+
+fn html(rows: &[Vec<Span>]) -> String {
+    let mut out = format!(
+        "<!DOCTYPE html>\n\
+         <html>\n\
+         <head>\n\
+         <meta charset=\"utf-8\">\n\
+         <title>{}</title>\n\
+         <style>\n",
+        env!("CARGO_PKG_NAME")
+    );
+    // Pushed piece by piece as cargo-generate renders this file with Liquid,
+    // which would take the escaped braces of a format string for a tag
+    for part in [
+        "body { margin: 0; background: ",
+        DEFAULT_BG,
+        "; }\npre { margin: 0; padding: 1em; color: ",
+        DEFAULT_FG,
+        "; background: ",
+        DEFAULT_BG,
+        "; font-family: ",
+        FONT_FAMILY,
+        "; line-height: 1.2; }\n</style>\n</head>\n<body>\n<pre>",
+    ] {
+        out.push_str(part);
+    }
+
+    for (y, row) in rows.iter().enumerate() {
+        if y > 0 {
+            out.push('\n');
+        }
+        for span in row {
+            let css = span_css(&span.style);
+            if css.is_empty() {
+                out.push_str(&escape(&span.text));
+            } else {
+                let _ = write!(out, "<span style=\"{css}\">{}</span>", escape(&span.text));
+            }
+        }
+    }
+
+    out.push_str("</pre>\n</body>\n</html>\n");
+    out
+}
+
+/// Inline CSS for a span, empty for the page's default style
+fn span_css(style: &CellStyle) -> String {
+    let (fg, bg) = style.css_colors();
+    let mut css = Vec::new();
+    if fg != DEFAULT_FG {
+        css.push(format!("color: {fg}"));
+    }
+    if bg != DEFAULT_BG {
+        css.push(format!("background-color: {bg}"));
+    }
+    if style.modifier.contains(Modifier::BOLD) {
+        css.push("font-weight: bold".into());
+    }
+    if style.modifier.contains(Modifier::DIM) {
+        css.push("opacity: 0.6".into());
+    }
+    if style.modifier.contains(Modifier::ITALIC) {
+        css.push("font-style: italic".into());
+    }
+    let decoration = text_decoration(style.modifier);
+    if !decoration.is_empty() {
+        css.push(format!("text-decoration: {decoration}"));
+    }
+    css.join("; ")
+}
+
+fn text_decoration(modifier: Modifier) -> String {
+    let mut decoration = Vec::new();
+    if modifier.contains(Modifier::UNDERLINED) {
+        decoration.push("underline");
+    }
+    if modifier.contains(Modifier::CROSSED_OUT) {
+        decoration.push("line-through");
+    }
+    decoration.join(" ")
+}
+
+fn svg(rows: &[Vec<Span>], width: u16, height: u16) -> String {
+    let width = f64::from(width) * CELL_WIDTH;
+    let height = f64::from(height) * CELL_HEIGHT;
+    let mut out = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width:.1}\" height=\"{height:.1}\" \
+         viewBox=\"0 0 {width:.1} {height:.1}\" font-family=\"{FONT_FAMILY}\" font-size=\"14\">\n\
+         <rect width=\"100%\" height=\"100%\" fill=\"{DEFAULT_BG}\"/>\n"
+    );
+
+    for (y, row) in rows.iter().enumerate() {
+        let top = y as f64 * CELL_HEIGHT;
+        for span in row {
+            let left = f64::from(span.x) * CELL_WIDTH;
+            let span_width = f64::from(span.width) * CELL_WIDTH;
+            let (fg, bg) = span.style.css_colors();
+
+            if bg != DEFAULT_BG {
+                let _ = writeln!(
+                    out,
+                    "<rect x=\"{left:.1}\" y=\"{top:.1}\" width=\"{span_width:.1}\" \
+                     height=\"{CELL_HEIGHT:.1}\" fill=\"{bg}\"/>"
+                );
+            }
+            if span.text.trim().is_empty() {
+                continue;
+            }
+
+            let mut attributes = format!("fill=\"{fg}\"");
+            if span.style.modifier.contains(Modifier::BOLD) {
+                attributes.push_str(" font-weight=\"bold\"");
+            }
+            if span.style.modifier.contains(Modifier::DIM) {
+                attributes.push_str(" opacity=\"0.6\"");
+            }
+            if span.style.modifier.contains(Modifier::ITALIC) {
+                attributes.push_str(" font-style=\"italic\"");
+            }
+            let decoration = text_decoration(span.style.modifier);
+            if !decoration.is_empty() {
+                let _ = write!(attributes, " text-decoration=\"{decoration}\"");
+            }
+            // Text sits on its baseline, a bit above the bottom of the cell;
+            // `textLength` keeps columns aligned whatever font is picked
+            let baseline = top + CELL_HEIGHT - 4.0;
+            let _ = writeln!(
+                out,
+                "<text x=\"{left:.1}\" y=\"{baseline:.1}\" textLength=\"{span_width:.1}\" \
+                 xml:space=\"preserve\" {attributes}>{}</text>",
+                escape(&span.text)
+            );
+        }
+    }
+
+    out.push_str("</svg>\n");
+    out
+}
+
+/// Escapes text for HTML and SVG
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::{layout::Rect, style::Style};
+
+    use super::*;
+
+    fn buffer(width: u16, height: u16) -> Buffer {
+        Buffer::empty(Rect::new(0, 0, width, height))
+    }
+
+    #[test]
+    fn text_trims_rows_and_keeps_wide_characters() {
+        let mut buffer = buffer(6, 2);
+        buffer.set_string(0, 0, "ab", Style::new().fg(Color::Red));
+        buffer.set_string(1, 1, "漢x", Style::new());
+
+        assert_eq!(render(&buffer, ScreenshotFormat::Text), "ab\n 漢x\n");
+    }
+
+    #[test]
+    fn ansi_sets_each_style() {
+        let mut buffer = buffer(3, 1);
+        let style = Style::new()
+            .fg(Color::Red)
+            .bg(Color::Rgb(1, 2, 3))
+            .add_modifier(Modifier::BOLD);
+        buffer.set_string(0, 0, "ab", style);
+
+        assert_eq!(
+            render(&buffer, ScreenshotFormat::Ansi),
+            "\x1b[0;1;31;48;2;1;2;3mab\x1b[0m \x1b[0m\n"
+        );
+    }
+
+    #[test]
+    fn html_escapes_text() {
+        let mut buffer = buffer(11, 1);
+        buffer.set_string(0, 0, "<b>&\"x\"</b>", Style::new());
+
+        let html = render(&buffer, ScreenshotFormat::Html);
+        assert!(html.contains("<pre>&lt;b&gt;&amp;&quot;x&quot;&lt;/b&gt;</pre>"));
+        assert!(!html.contains("<b>"));
+    }
+
+    #[test]
+    fn html_styles_spans() {
+        let mut buffer = buffer(4, 1);
+        let style = Style::new()
+            .fg(Color::Red)
+            .bg(Color::Indexed(21))
+            .add_modifier(Modifier::BOLD | Modifier::UNDERLINED);
+        buffer.set_string(1, 0, "<>", style);
+        buffer.set_string(3, 0, "r", Style::new().add_modifier(Modifier::REVERSED));
+
+        let html = render(&buffer, ScreenshotFormat::Html);
+        assert!(html.contains(
+            "<pre> <span style=\"color: #cd0000; background-color: #0000ff; \
+             font-weight: bold; text-decoration: underline\">&lt;&gt;</span>\
+             <span style=\"color: #000000; background-color: #e5e5e5\">r</span></pre>"
+        ));
+    }
+
+    #[test]
+    fn svg_draws_styled_cells() {
+        let mut buffer = buffer(5, 2);
+        let style = Style::new()
+            .fg(Color::Rgb(255, 128, 0))
+            .bg(Color::Blue)
+            .add_modifier(Modifier::ITALIC);
+        buffer.set_string(2, 1, "a&b", style);
+
+        let svg = render(&buffer, ScreenshotFormat::Svg);
+        assert!(svg.starts_with(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"42.0\" height=\"36.0\""
+        ));
+        assert!(svg.contains(
+            "<rect x=\"16.8\" y=\"18.0\" width=\"25.2\" height=\"18.0\" fill=\"#0000ee\"/>"
+        ));
+        assert!(svg.contains(
+            "<text x=\"16.8\" y=\"32.0\" textLength=\"25.2\" xml:space=\"preserve\" \
+             fill=\"#ff8000\" font-style=\"italic\">a&amp;b</text>"
+        ));
+        assert!(svg.ends_with("</svg>\n"));
+    }
+
+    #[test]
+    fn svg_skips_blank_default_cells() {
+        let svg = render(&buffer(3, 1), ScreenshotFormat::Svg);
+        assert!(!svg.contains("<text"));
+        assert_eq!(svg.matches("<rect").count(), 1);
+    }
+}
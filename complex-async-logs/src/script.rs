@@ -0,0 +1,273 @@
+//! User scripts in [Rhai](https://rhai.rs), loaded from `init.rhai` in the
+//! config directory, e.g. `~/.config/<app>/init.rhai`
+//!
+//! The script runs once at startup, and its functions run again when a key
+//! bound to them with `bind` is pressed:
+//!
+//! ```rhai
+//! fn errors_only() {
+//!     show("logs");
+//!     press("1");
+//!     notify(`${state().logs.records} errors`);
+//! }
+//!
+//! bind("F5", "errors_only");
+//! ```
+//!
+//! Scripts act on the app by sending events, which are handled once the
+//! script returns, so `state()` shows the screens as they were when it
+//! started. Available functions:
+//!
+//! - `notify(text)`, `warn(text)`, `error(text)` show a toast
+//! - `show(screen)` switches to `"main"`, `"logs"` or `"notifications"`
+//! - `press(key)` sends a key press, named as for `bind`. A function bound to
+//!   a key may press keys that run functions in turn, but only up to
+//!   8 presses deep, so a function pressing its own key stops there
+//! - `screenshot(format)` saves a `"text"`, `"ansi"`, `"html"` or `"svg"`
+//!   screenshot
+//! - `copy(text)` puts text on the clipboard
+//! - `set_rates(frame_rate, tick_rate)` changes how often frames and ticks
+//!   happen
+//! - `quit()` ends the app
+//! - `state()` returns a map with the current `screen`, the rates and what
+//!   each screen exposes under its name
+//! - `bind(key, function)` calls the function, which takes no arguments, when
+//!   `key` is pressed on any screen. Keys are a single character, `F1` to
+//!   `F12` or names like `Enter`, `Esc` and `PageDown`. Only function keys
+//!   work while typing into a text field, such as the log search. `F12`
+//!   always toggles the debug overlay and can't be bound.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU8, Ordering},
+        Arc, Mutex, MutexGuard, PoisonError,
+    },
+};
+
+use rhai::{CallFnOptions, Dynamic, Engine, EvalAltResult, Map, Scope, AST};
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::{
+    app::DEBUG_OVERLAY_KEY,
+    backend::KeyCode,
+    paths,
+    screen_manager::{parse_key, Action, ScreenState},
+    Event, Notifier, Rates, ScreenshotFormat,
+};
+
+/// Stops scripts stuck in a loop, as they run on the UI thread
+const MAX_OPERATIONS: u64 = 1_000_000;
+/// How many key presses by scripts may lead to one another, stops a function
+/// pressing its own key from running forever
+const MAX_PRESS_DEPTH: u8 = 8;
+
+type ScriptResult = Result<(), Box<EvalAltResult>>;
+
+/// Path of the script loaded at startup
+pub fn init_script() -> PathBuf {
+    paths::config_dir().join("init.rhai")
+}
+
+/// A compiled script and the engine running it
+pub struct Scripts {
+    engine: Engine,
+    ast: AST,
+    /// Returned by `state()`, set before the script runs
+    state: Arc<Mutex<Map>>,
+    /// Collected from `bind` while the script runs
+    bindings: Arc<Mutex<Vec<(KeyCode, String)>>>,
+    /// Number of presses by scripts that led to the running call
+    depth: Arc<AtomicU8>,
+}
+
+impl Scripts {
+    pub fn load(path: &Path, event_tx: UnboundedSender<Event>) -> Result<Self, Box<EvalAltResult>> {
+        let state = Arc::default();
+        let bindings = Arc::default();
+        let depth = Arc::default();
+        let engine = engine(
+            event_tx,
+            Arc::clone(&state),
+            Arc::clone(&bindings),
+            Arc::clone(&depth),
+        );
+        let ast = engine.compile_file(path.to_path_buf())?;
+
+        Ok(Self {
+            engine,
+            ast,
+            state,
+            bindings,
+            depth,
+        })
+    }
+
+    /// Runs the statements outside functions, seeing `state`
+    pub fn run(&mut self, state: Map) -> ScriptResult {
+        *lock(&self.state) = state;
+        self.depth.store(0, Ordering::Relaxed);
+        self.engine.run_ast(&self.ast)
+    }
+
+    /// Calls the function `name`, seeing `state`, for a key pressed `depth`
+    /// presses deep by scripts, 0 if the user pressed it
+    pub fn call(&mut self, name: &str, state: Map, depth: u8) -> ScriptResult {
+        *lock(&self.state) = state;
+        self.depth.store(depth, Ordering::Relaxed);
+        let options = CallFnOptions::new().eval_ast(false);
+        self.engine
+            .call_fn_with_options::<Dynamic>(options, &mut Scope::new(), &self.ast, name, ())
+            .map(|_| ())
+    }
+
+    pub fn has_function(&self, name: &str) -> bool {
+        self.ast
+            .iter_functions()
+            .any(|function| function.name == name && function.params.is_empty())
+    }
+
+    /// Keys bound by the script since the last call
+    pub fn take_bindings(&self) -> Vec<(KeyCode, String)> {
+        std::mem::take(&mut *lock(&self.bindings))
+    }
+}
+
+fn engine(
+    event_tx: UnboundedSender<Event>,
+    state: Arc<Mutex<Map>>,
+    bindings: Arc<Mutex<Vec<(KeyCode, String)>>>,
+    depth: Arc<AtomicU8>,
+) -> Engine {
+    let mut engine = Engine::new();
+    engine.set_max_operations(MAX_OPERATIONS);
+
+    let notifier = Notifier::new(event_tx.clone());
+    let info = notifier.clone();
+    engine.register_fn("notify", move |text: &str| info.info(text));
+    let warn = notifier.clone();
+    engine.register_fn("warn", move |text: &str| warn.warn(text));
+    engine.register_fn("error", move |text: &str| notifier.error(text));
+
+    let send = move |event: Event| {
+        // Only fails once the app is shutting down
+        let _ = event_tx.send(event);
+    };
+
+    let tx = send.clone();
+    engine.register_fn("show", move |name: &str| -> ScriptResult {
+        let screen = ScreenState::from_name(name).ok_or(format!("unknown screen {name:?}"))?;
+        tx(Event::Action(Action::Show(screen)));
+        Ok(())
+    });
+    let tx = send.clone();
+    engine.register_fn("press", move |name: &str| -> ScriptResult {
+        let depth = depth.load(Ordering::Relaxed) + 1;
+        if depth > MAX_PRESS_DEPTH {
+            return Err(format!(
+                "press({name:?}): more than {MAX_PRESS_DEPTH} presses by scripts in a row, \
+                 does a function press its own key?"
+            )
+            .into());
+        }
+        tx(Event::ScriptKey(key(name)?, depth));
+        Ok(())
+    });
+    let tx = send.clone();
+    engine.register_fn("screenshot", move |name: &str| -> ScriptResult {
+        let format = match name {
+            "text" => ScreenshotFormat::Text,
+            "ansi" => ScreenshotFormat::Ansi,
+            "html" => ScreenshotFormat::Html,
+            "svg" => ScreenshotFormat::Svg,
+            _ => return Err(format!("unknown screenshot format {name:?}").into()),
+        };
+        tx(Event::Screenshot(format));
+        Ok(())
+    });
+    let tx = send.clone();
+    engine.register_fn("copy", move |text: &str| tx(Event::Copy(text.to_string())));
+    let tx = send.clone();
+    engine.register_fn("set_rates", move |frame_rate: f64, tick_rate: f64| {
+        tx(Event::SetRates(Rates {
+            frame_rate,
+            tick_rate,
+        }));
+    });
+    let tx = send.clone();
+    engine.register_fn("set_rates", move |frame_rate: i64, tick_rate: i64| {
+        tx(Event::SetRates(Rates {
+            frame_rate: frame_rate as f64,
+            tick_rate: tick_rate as f64,
+        }));
+    });
+    engine.register_fn("quit", move || send(Event::Quit));
+
+    engine.register_fn("state", move || lock(&state).clone());
+    engine.register_fn("bind", move |name: &str, function: &str| -> ScriptResult {
+        let key = key(name)?;
+        if key == DEBUG_OVERLAY_KEY {
+            return Err(format!("bind({name:?}): reserved for the debug overlay").into());
+        }
+        lock(&bindings).push((key, function.to_string()));
+        Ok(())
+    });
+
+    engine
+}
+
+fn key(name: &str) -> Result<KeyCode, Box<EvalAltResult>> {
+    parse_key(name).ok_or_else(|| format!("unknown key {name:?}").into())
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver};
+
+    use super::*;
+
+    fn load(source: &str) -> (Scripts, UnboundedReceiver<Event>) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("init.rhai");
+        std::fs::write(&path, source).unwrap();
+        let (event_tx, event_rx) = unbounded_channel();
+        (Scripts::load(&path, event_tx).unwrap(), event_rx)
+    }
+
+    #[test]
+    fn presses_count_how_deep_they_go() {
+        let (mut scripts, mut events) = load(r#"fn again() { press("F5"); }"#);
+
+        scripts.call("again", Map::new(), 0).unwrap();
+        assert!(matches!(
+            events.try_recv(),
+            Ok(Event::ScriptKey(KeyCode::F(5), 1))
+        ));
+
+        scripts.call("again", Map::new(), 3).unwrap();
+        assert!(matches!(
+            events.try_recv(),
+            Ok(Event::ScriptKey(KeyCode::F(5), 4))
+        ));
+    }
+
+    #[test]
+    fn stops_a_function_pressing_its_own_key() {
+        let (mut scripts, mut events) = load(r#"fn again() { press("F5"); }"#);
+
+        assert!(scripts.call("again", Map::new(), MAX_PRESS_DEPTH).is_err());
+        assert!(events.try_recv().is_err());
+    }
+
+    #[test]
+    fn refuses_to_bind_the_debug_overlay_key() {
+        let (mut scripts, _events) = load(r#"fn overlay() {} bind("F12", "overlay");"#);
+
+        assert!(scripts.run(Map::new()).is_err());
+        assert!(scripts.take_bindings().is_empty());
+    }
+}
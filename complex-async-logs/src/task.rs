@@ -0,0 +1,24 @@
+use std::future::Future;
+
+use tokio::task::JoinHandle;
+
+use crate::Result;
+
+/// Spawns `future` on the runtime under `name`, which `tokio-console` shows
+/// in its task list
+pub fn spawn_named<F>(name: &str, future: F) -> Result<JoinHandle<F::Output>>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    #[cfg(all(tokio_unstable, feature = "console"))]
+    let handle = tokio::task::Builder::new().name(name).spawn(future)?;
+
+    #[cfg(not(all(tokio_unstable, feature = "console")))]
+    let handle = {
+        let _ = name;
+        tokio::spawn(future)
+    };
+
+    Ok(handle)
+}
@@ -1,14 +1,16 @@
-use std::{io::Stdout, time::Duration};
+use std::time::{Duration, Instant};
 
-use crossterm::event::{
-    Event as CrosstermEvent, EventStream as CrosstermEventStream, KeyCode, KeyEventKind,
-};
-use futures::{FutureExt, StreamExt};
-use ratatui::{prelude::CrosstermBackend, Terminal};
+use ratatui::Terminal;
 use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
 
-use crate::{error::Error, Result, ScreenManager};
+use crate::{
+    backend::{self, Backend, EventSource, KeyCode},
+    debug_overlay::DebugOverlay,
+    error::Error,
+    Context, Result, ScreenManager,
+};
 
+#[derive(Debug)]
 pub enum Event {
     Key(KeyCode),
     Tick,
@@ -16,40 +18,62 @@ pub enum Event {
     Quit,
 }
 
+impl Event {
+    /// Name of the variant, e.g. for the debug overlay
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Event::Key(_) => "key",
+            Event::Tick => "tick",
+            Event::Frame => "frame",
+            Event::Quit => "quit",
+        }
+    }
+}
+
 pub struct App {
     should_quit: bool,
-    crossterm_event: CrosstermEventStream,
+    input: EventSource,
     frame_rate: f64,
     tick_rate: f64,
     event_tx: UnboundedSender<Event>,
     event_rx: UnboundedReceiver<Event>,
-    terminal: Terminal<CrosstermBackend<Stdout>>,
+    terminal: Terminal<Backend>,
     screen_manager: ScreenManager,
+    debug_overlay: DebugOverlay,
 }
 
 impl App {
     pub async fn new(frame_rate: f64, tick_rate: f64) -> Result<Self> {
         let (event_tx, event_rx) = unbounded_channel();
         let screen_manager = ScreenManager::new(event_tx.clone()).await?;
-        let terminal = Terminal::new(CrosstermBackend::new(std::io::stdout()))?;
+        let terminal = backend::terminal().context("creating the terminal")?;
+        let input = EventSource::new().context("reading terminal input")?;
 
         Ok(Self {
             should_quit: false,
-            crossterm_event: CrosstermEventStream::new(),
+            input,
             frame_rate,
             tick_rate,
             event_tx,
             event_rx,
             screen_manager,
+            debug_overlay: DebugOverlay::new(),
             terminal,
         })
     }
 
+    /// Runs the UI until quit, restoring the terminal even if it fails
     pub async fn run(&mut self) -> Result<()> {
-        startup()?;
+        let mut result = backend::startup(&mut self.terminal).context("setting up the terminal");
+
+        if result.is_ok() {
+            result = self.event_loop().await;
+        }
 
-        //let mut terminal = Terminal::new(CrosstermBackend::new(std::io::stdout()))?;
+        result.and(backend::shutdown(&mut self.terminal).context("restoring the terminal"))
+    }
 
+    async fn event_loop(&mut self) -> Result<()> {
         let frame_rate = Duration::from_secs_f64(1.0 / self.frame_rate);
         let tick_rate = Duration::from_secs_f64(1.0 / self.tick_rate);
         let mut frame_interval = tokio::time::interval(frame_rate);
@@ -63,15 +87,11 @@ impl App {
                 _frame = frame_interval.tick() => {
                     self.event_tx.send(Event::Frame)?;
                 }
-                event = self.crossterm_event.next().fuse() => {
-                    match event.ok_or(Error::CrosstermEvent)?? {
-                        CrosstermEvent::Key(key) => {
-                            if let KeyEventKind::Press = key.kind {
-                                self.event_tx.send(Event::Key(key.code))?;
-                            }
-                        }
-                        _ => {}
-                    }
+                key = self.input.next_key() => {
+                    let key = key
+                        .ok_or(Error::Input)?
+                        .context("reading terminal input")?;
+                    self.event_tx.send(Event::Key(key))?;
                 }
                 Some(event) = self.event_rx.recv() => {
                     self.handle_event(event).await?;
@@ -80,16 +100,36 @@ impl App {
             }
         }
 
-        shutdown()
+        Ok(())
     }
 
     async fn handle_event(&mut self, event: Event) -> Result<()> {
+        match &event {
+            Event::Tick => self.debug_overlay.record_tick(),
+            // Frames are counted once they have been drawn
+            Event::Frame => {}
+            event => self.debug_overlay.record_event(event.kind()),
+        }
+
         match event {
+            Event::Key(KeyCode::F(12)) => self.debug_overlay.toggle(),
             Event::Key(key) => self.screen_manager.handle_key(key).await?,
             Event::Frame => {
-                self.terminal.draw(|frame| {
-                    self.screen_manager.draw(frame.area(), frame);
-                })?;
+                let queue_depth = self.event_rx.len();
+                let started = Instant::now();
+                self.terminal
+                    .draw(|frame| {
+                        self.screen_manager.draw(frame.area(), frame);
+                        self.debug_overlay.draw(
+                            frame.area(),
+                            frame,
+                            queue_depth,
+                            self.frame_rate,
+                            self.tick_rate,
+                        );
+                    })
+                    .context("drawing a frame")?;
+                self.debug_overlay.record_frame(started.elapsed());
             }
             Event::Quit => self.should_quit = true,
             _ => {}
@@ -97,15 +137,3 @@ impl App {
         Ok(())
     }
 }
-
-fn startup() -> Result<()> {
-    crossterm::terminal::enable_raw_mode()?;
-    crossterm::execute!(std::io::stdout(), crossterm::terminal::EnterAlternateScreen)?;
-    Ok(())
-}
-
-fn shutdown() -> Result<()> {
-    crossterm::terminal::disable_raw_mode()?;
-    crossterm::execute!(std::io::stdout(), crossterm::terminal::LeaveAlternateScreen)?;
-    Ok(())
-}
@@ -1,17 +1,75 @@
+use std::{fmt::Write, process::ExitCode};
+
 use thiserror::Error;
 
 use crate::app;
 
+/// Exit codes follow sysexits.h where one fits
+const EXIT_SOFTWARE: u8 = 70;
+const EXIT_IOERR: u8 = 74;
+
 #[derive(Debug, Error)]
 pub enum Error {
-    #[error("CrosstermEvent error")]
-    CrosstermEvent,
-    #[error("JoinError")]
+    #[error("terminal input stream closed")]
+    Input,
+    #[error("background task failed: {0}")]
     JoinError(#[from] tokio::task::JoinError),
-    #[error("")]
+    #[error("event loop is no longer running, an event could not be delivered")]
     SendError(#[from] tokio::sync::mpsc::error::SendError<app::Event>),
-    #[error("IO: {0}")]
+    #[error("I/O error: {0}")]
     IO(#[from] std::io::Error),
-    #[error("Unexpected: {0}")]
+    #[error("unexpected error: {0}")]
     Unexpected(String),
+    /// What was being done when `source` happened
+    #[error("{context}")]
+    Context { context: String, source: Box<Error> },
+}
+
+impl Error {
+    /// Process exit code for this error, looking through any context
+    pub fn exit_code(&self) -> ExitCode {
+        let code = match self.root_cause() {
+            Error::Input | Error::IO(_) => EXIT_IOERR,
+            _ => EXIT_SOFTWARE,
+        };
+        ExitCode::from(code)
+    }
+
+    /// Human readable description including the chain of causes
+    pub fn report(&self) -> String {
+        let mut report = format!("Error: {self}");
+        let mut current = self;
+        while let Error::Context { source, .. } = current {
+            let _ = write!(report, "\n  caused by: {source}");
+            current = source;
+        }
+        report
+    }
+
+    fn root_cause(&self) -> &Error {
+        match self {
+            Error::Context { source, .. } => source.root_cause(),
+            error => error,
+        }
+    }
+}
+
+/// Adds a description of the failed operation to an error
+pub trait Context<T> {
+    fn context(self, context: impl Into<String>) -> crate::Result<T>;
+
+    fn with_context<C: Into<String>>(self, context: impl FnOnce() -> C) -> crate::Result<T>;
+}
+
+impl<T, E: Into<Error>> Context<T> for std::result::Result<T, E> {
+    fn context(self, context: impl Into<String>) -> crate::Result<T> {
+        self.with_context(|| context)
+    }
+
+    fn with_context<C: Into<String>>(self, context: impl FnOnce() -> C) -> crate::Result<T> {
+        self.map_err(|error| Error::Context {
+            context: context().into(),
+            source: Box::new(error.into()),
+        })
+    }
 }
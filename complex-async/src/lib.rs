@@ -1,8 +1,11 @@
 mod app;
+mod backend;
+mod debug_overlay;
 mod error;
 mod screen_manager;
 
 pub use app::{App, Event};
+pub use error::{Context, Error};
 pub use screen_manager::ScreenManager;
 
 pub type Result<T> = std::result::Result<T, error::Error>;
@@ -1,6 +1,31 @@
-use {{project-name}}::{App, Result};
+use std::process::ExitCode;
+
+use {{project-name}}::{App, Context, Result};
 
 #[tokio::main]
-async fn main() -> Result<()> {
-    App::new(60f64, 10f64).await?.run().await
+async fn main() -> ExitCode {
+    match run().await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{}", err.report());
+            err.exit_code()
+        }
+    }
+}
+
+async fn run() -> Result<()> {
+    // Attach with `tokio-console` while the app is running
+    #[cfg(feature = "console")]
+    {
+        use tracing_subscriber::prelude::*;
+        tracing_subscriber::registry()
+            .with(console_subscriber::spawn())
+            .init();
+    }
+
+    App::new(60f64, 10f64)
+        .await
+        .context("starting the app")?
+        .run()
+        .await
 }
@@ -1,8 +1,7 @@
-use crossterm::event::KeyCode;
 use ratatui::{layout::Rect, Frame};
 use tokio::sync::mpsc::UnboundedSender;
 
-use crate::{Event, Result};
+use crate::{backend::KeyCode, Context, Event, Result};
 use main::Main;
 
 mod main;
@@ -28,13 +27,17 @@ impl ScreenManager {
         Ok(Self {
             screen_state: ScreenState::Main,
             command_tx,
-            main: Main::new().await?,
+            main: Main::new().await.context("setting up the main screen")?,
         })
     }
 
     pub async fn handle_key(&mut self, keycode: KeyCode) -> Result<()> {
         let screen_event = match self.screen_state {
-            ScreenState::Main => self.main.handle_key(keycode).await?,
+            ScreenState::Main => self
+                .main
+                .handle_key(keycode)
+                .await
+                .with_context(|| format!("main screen: handling {keycode:?}"))?,
         };
 
         match screen_event {
@@ -1,7 +1,6 @@
-use crossterm::event::KeyCode;
 use ratatui::{layout::Rect, widgets::Paragraph, Frame};
 
-use crate::Result;
+use crate::{backend::KeyCode, Result};
 
 use super::ScreenEvent;
 
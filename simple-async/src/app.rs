@@ -1,11 +1,13 @@
-use std::{io::Stdout, time::Duration};
+use std::time::{Duration, Instant};
 
-use crossterm::event::{Event as CrosstermEvent, EventStream, KeyCode, KeyEventKind};
-use futures::{FutureExt, StreamExt};
-use ratatui::{prelude::CrosstermBackend, widgets::Paragraph, Terminal};
+use ratatui::{widgets::Paragraph, Terminal};
 use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
 
-use crate::{Error, Result};
+use crate::{
+    backend::{self, Backend, EventSource, KeyCode},
+    debug_overlay::DebugOverlay,
+    Context, Error, Result,
+};
 
 #[derive(Debug)]
 pub enum Event {
@@ -14,22 +16,33 @@ pub enum Event {
     Frame,
 }
 
-#[derive(Debug)]
+impl Event {
+    /// Name of the variant, e.g. for the debug overlay
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Event::Key(_) => "key",
+            Event::Tick => "tick",
+            Event::Frame => "frame",
+        }
+    }
+}
+
 pub struct App {
     should_quit: bool,
-    crossterm_event: EventStream,
+    input: EventSource,
     frame_rate: f64,
     tick_rate: f64,
     event_rx: UnboundedReceiver<Event>,
     event_tx: UnboundedSender<Event>,
-    terminal: Terminal<CrosstermBackend<Stdout>>,
+    terminal: Terminal<Backend>,
+    debug_overlay: DebugOverlay,
 }
 
 impl App {
     pub fn new(frame_rate: f64, tick_rate: f64) -> Result<Self> {
         let (event_tx, event_rx) = unbounded_channel();
-        let crossterm_event = EventStream::new();
-        let terminal = Terminal::new(CrosstermBackend::new(std::io::stdout()))?;
+        let terminal = backend::terminal().context("creating the terminal")?;
+        let input = EventSource::new().context("reading terminal input")?;
 
         Ok(Self {
             should_quit: false,
@@ -37,14 +50,24 @@ impl App {
             event_tx,
             frame_rate,
             tick_rate,
-            crossterm_event,
+            input,
             terminal,
+            debug_overlay: DebugOverlay::new(),
         })
     }
 
+    /// Runs the UI until quit, restoring the terminal even if it fails
     pub async fn run(&mut self) -> Result<()> {
-        startup()?;
+        let mut result = backend::startup(&mut self.terminal).context("setting up the terminal");
+
+        if result.is_ok() {
+            result = self.event_loop().await;
+        }
+
+        result.and(backend::shutdown(&mut self.terminal).context("restoring the terminal"))
+    }
 
+    async fn event_loop(&mut self) -> Result<()> {
         let frame_rate = Duration::from_secs_f64(1.0 / self.frame_rate);
         let tick_rate = Duration::from_secs_f64(1.0 / self.tick_rate);
         let mut frame_interval = tokio::time::interval(frame_rate);
@@ -53,38 +76,52 @@ impl App {
         while !self.should_quit {
             tokio::select! {
                 _tick = tick_interval.tick() => {
-                    self.event_tx.send(Event::Tick).unwrap();
+                    self.event_tx.send(Event::Tick)?;
                 }
                 _frame = frame_interval.tick() => {
-                    self.event_tx.send(Event::Frame).unwrap();
+                    self.event_tx.send(Event::Frame)?;
                 }
                 Some(event) = self.event_rx.recv() => {
                     self.handle_event(&event)?;
                 }
-                event = self.crossterm_event.next().fuse() => {
-                    match event.ok_or(Error::Crossterm)?? {
-                        CrosstermEvent::Key(key) => {
-                            if let KeyEventKind::Press = key.kind {
-                                self.event_tx.send(Event::Key(key.code))?
-                            }
-                        }
-                        _ => {}
-                    }
+                key = self.input.next_key() => {
+                    let key = key
+                        .ok_or(Error::Input)?
+                        .context("reading terminal input")?;
+                    self.event_tx.send(Event::Key(key))?;
                 }
             }
         }
 
-        shutdown()?;
         Ok(())
     }
 
     fn handle_event(&mut self, event: &Event) -> Result<()> {
+        match event {
+            Event::Tick => self.debug_overlay.record_tick(),
+            // Frames are counted once they have been drawn
+            Event::Frame => {}
+            event => self.debug_overlay.record_event(event.kind()),
+        }
+
         match event {
             Event::Key(key) => self.handle_key_event(key)?,
             Event::Frame => {
-                self.terminal.draw(|frame| {
-                    frame.render_widget(Paragraph::new("Hi"), frame.area());
-                })?;
+                let queue_depth = self.event_rx.len();
+                let started = Instant::now();
+                self.terminal
+                    .draw(|frame| {
+                        frame.render_widget(Paragraph::new("Hi"), frame.area());
+                        self.debug_overlay.draw(
+                            frame.area(),
+                            frame,
+                            queue_depth,
+                            self.frame_rate,
+                            self.tick_rate,
+                        );
+                    })
+                    .context("drawing a frame")?;
+                self.debug_overlay.record_frame(started.elapsed());
             }
             _ => {}
         }
@@ -95,21 +132,10 @@ impl App {
     fn handle_key_event(&mut self, keycode: &KeyCode) -> Result<()> {
         match keycode {
             KeyCode::Char('q') => self.should_quit = true,
+            KeyCode::F(12) => self.debug_overlay.toggle(),
             _ => {}
         }
 
         Ok(())
     }
 }
-
-fn startup() -> Result<()> {
-    crossterm::terminal::enable_raw_mode()?;
-    crossterm::execute!(std::io::stdout(), crossterm::terminal::EnterAlternateScreen)?;
-    Ok(())
-}
-
-fn shutdown() -> Result<()> {
-    crossterm::terminal::disable_raw_mode()?;
-    crossterm::execute!(std::io::stdout(), crossterm::terminal::LeaveAlternateScreen)?;
-    Ok(())
-}
@@ -0,0 +1,95 @@
+use std::{
+    io::{self, Stdout, Write},
+    thread,
+};
+
+use ratatui::{prelude::TermionBackend, Terminal};
+use termion::{
+    event::Key,
+    input::TermRead,
+    raw::{IntoRawMode, RawTerminal},
+    screen::{ToAlternateScreen, ToMainScreen},
+};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver};
+
+use super::KeyCode;
+
+pub type Backend = TermionBackend<RawTerminal<Stdout>>;
+
+pub fn terminal() -> io::Result<Terminal<Backend>> {
+    // termion only offers raw mode through the writer, which starts in it
+    let stdout = io::stdout().into_raw_mode()?;
+    stdout.suspend_raw_mode()?;
+    Terminal::new(TermionBackend::new(stdout))
+}
+
+pub fn startup(terminal: &mut Terminal<Backend>) -> io::Result<()> {
+    let stdout = terminal.backend_mut().writer_mut();
+    stdout.activate_raw_mode()?;
+    write!(stdout, "{ToAlternateScreen}")?;
+    stdout.flush()
+}
+
+pub fn shutdown(terminal: &mut Terminal<Backend>) -> io::Result<()> {
+    let stdout = terminal.backend_mut().writer_mut();
+    write!(stdout, "{ToMainScreen}")?;
+    stdout.flush()?;
+    stdout.suspend_raw_mode()
+}
+
+/// Keys read from stdin on a separate thread, as termion only reads blocking
+pub struct EventSource {
+    keys: UnboundedReceiver<io::Result<KeyCode>>,
+}
+
+impl EventSource {
+    pub fn new() -> io::Result<Self> {
+        let (tx, keys) = unbounded_channel();
+        thread::Builder::new()
+            .name("termion-input".to_string())
+            .spawn(move || {
+                for key in io::stdin().keys() {
+                    let key = match key {
+                        Ok(key) => match convert_key(key) {
+                            Some(key) => Ok(key),
+                            None => continue,
+                        },
+                        Err(err) => Err(err),
+                    };
+                    if tx.send(key).is_err() {
+                        break;
+                    }
+                }
+            })?;
+        Ok(Self { keys })
+    }
+
+    /// Waits for the next key press, `None` once input is closed
+    pub async fn next_key(&mut self) -> Option<io::Result<KeyCode>> {
+        self.keys.recv().await
+    }
+}
+
+fn convert_key(key: Key) -> Option<KeyCode> {
+    let key = match key {
+        Key::Char('\n') => KeyCode::Enter,
+        Key::Char('\t') => KeyCode::Tab,
+        Key::Char(c) => KeyCode::Char(c),
+        Key::F(n) => KeyCode::F(n),
+        Key::Esc => KeyCode::Esc,
+        Key::BackTab => KeyCode::BackTab,
+        Key::Backspace => KeyCode::Backspace,
+        Key::Delete => KeyCode::Delete,
+        Key::Insert => KeyCode::Insert,
+        Key::Up => KeyCode::Up,
+        Key::Down => KeyCode::Down,
+        Key::Left => KeyCode::Left,
+        Key::Right => KeyCode::Right,
+        Key::Home => KeyCode::Home,
+        Key::End => KeyCode::End,
+        Key::PageUp => KeyCode::PageUp,
+        Key::PageDown => KeyCode::PageDown,
+        _ => return None,
+    };
+    Some(key)
+}
@@ -0,0 +1,96 @@
+use std::{io, thread};
+
+use ratatui::{prelude::TermwizBackend, Terminal};
+use termwiz::{
+    caps::Capabilities,
+    input::{InputEvent, KeyCode as TermwizKeyCode},
+    surface::{Change, CursorVisibility},
+    terminal::{buffered::BufferedTerminal, SystemTerminal, Terminal as _},
+};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver};
+
+use super::KeyCode;
+
+pub type Backend = TermwizBackend;
+
+pub fn terminal() -> io::Result<Terminal<Backend>> {
+    // `TermwizBackend::new` would enter raw mode and the alternate screen
+    // right away, leave that to `startup`
+    let caps = Capabilities::new_from_env().map_err(io::Error::other)?;
+    let system = SystemTerminal::new_from_stdio(caps).map_err(io::Error::other)?;
+    let buffered = BufferedTerminal::new(system).map_err(io::Error::other)?;
+    Terminal::new(TermwizBackend::with_buffered_terminal(buffered))
+}
+
+pub fn startup(terminal: &mut Terminal<Backend>) -> io::Result<()> {
+    let system = terminal.backend_mut().buffered_terminal_mut().terminal();
+    system.set_raw_mode().map_err(io::Error::other)?;
+    system.enter_alternate_screen().map_err(io::Error::other)
+}
+
+pub fn shutdown(terminal: &mut Terminal<Backend>) -> io::Result<()> {
+    let buffered = terminal.backend_mut().buffered_terminal_mut();
+    buffered.add_change(Change::CursorVisibility(CursorVisibility::Visible));
+    buffered.flush().map_err(io::Error::other)?;
+    let system = buffered.terminal();
+    system.exit_alternate_screen().map_err(io::Error::other)?;
+    system.set_cooked_mode().map_err(io::Error::other)
+}
+
+/// Keys polled on a separate thread from a second handle to the terminal, so
+/// the one drawing never blocks on input
+pub struct EventSource {
+    keys: UnboundedReceiver<io::Result<KeyCode>>,
+}
+
+impl EventSource {
+    pub fn new() -> io::Result<Self> {
+        let caps = Capabilities::new_from_env().map_err(io::Error::other)?;
+        let mut input = SystemTerminal::new_from_stdio(caps).map_err(io::Error::other)?;
+        let (tx, keys) = unbounded_channel();
+        thread::Builder::new()
+            .name("termwiz-input".to_string())
+            .spawn(move || loop {
+                let key = match input.poll_input(None) {
+                    Ok(Some(InputEvent::Key(key))) => match convert_key(key.key) {
+                        Some(key) => Ok(key),
+                        None => continue,
+                    },
+                    Ok(_) => continue,
+                    Err(err) => Err(io::Error::other(err)),
+                };
+                if tx.send(key).is_err() {
+                    break;
+                }
+            })?;
+        Ok(Self { keys })
+    }
+
+    /// Waits for the next key press, `None` once input is closed
+    pub async fn next_key(&mut self) -> Option<io::Result<KeyCode>> {
+        self.keys.recv().await
+    }
+}
+
+fn convert_key(key: TermwizKeyCode) -> Option<KeyCode> {
+    let key = match key {
+        TermwizKeyCode::Char(c) => KeyCode::Char(c),
+        TermwizKeyCode::Function(n) => KeyCode::F(n),
+        TermwizKeyCode::Enter => KeyCode::Enter,
+        TermwizKeyCode::Escape => KeyCode::Esc,
+        TermwizKeyCode::Tab => KeyCode::Tab,
+        TermwizKeyCode::Backspace => KeyCode::Backspace,
+        TermwizKeyCode::Delete => KeyCode::Delete,
+        TermwizKeyCode::Insert => KeyCode::Insert,
+        TermwizKeyCode::UpArrow => KeyCode::Up,
+        TermwizKeyCode::DownArrow => KeyCode::Down,
+        TermwizKeyCode::LeftArrow => KeyCode::Left,
+        TermwizKeyCode::RightArrow => KeyCode::Right,
+        TermwizKeyCode::Home => KeyCode::Home,
+        TermwizKeyCode::End => KeyCode::End,
+        TermwizKeyCode::PageUp => KeyCode::PageUp,
+        TermwizKeyCode::PageDown => KeyCode::PageDown,
+        _ => return None,
+    };
+    Some(key)
+}
@@ -0,0 +1,149 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use ratatui::{
+    layout::Rect,
+    text::Line,
+    widgets::{Block, Clear, Paragraph},
+    Frame,
+};
+
+/// Number of frame render times kept for the percentiles
+const FRAME_SAMPLES: usize = 240;
+/// Number of recent events listed in the overlay
+const RECENT_EVENTS: usize = 8;
+
+/// Toggleable overlay with frame, tick and event queue statistics
+///
+/// Events are listed by kind only, so pasted and copied text never ends up on
+/// screen, or in a screenshot or recording of it.
+pub struct DebugOverlay {
+    visible: bool,
+    frame_times: VecDeque<Duration>,
+    recent_events: VecDeque<&'static str>,
+    window_start: Instant,
+    frames: u32,
+    ticks: u32,
+    fps: f64,
+    tps: f64,
+}
+
+impl DebugOverlay {
+    pub fn new() -> Self {
+        Self {
+            visible: false,
+            frame_times: VecDeque::with_capacity(FRAME_SAMPLES),
+            recent_events: VecDeque::with_capacity(RECENT_EVENTS),
+            window_start: Instant::now(),
+            frames: 0,
+            ticks: 0,
+            fps: 0.0,
+            tps: 0.0,
+        }
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    pub fn set_visible(&mut self, visible: bool) {
+        self.visible = visible;
+    }
+
+    pub fn toggle(&mut self) {
+        self.set_visible(!self.is_visible());
+    }
+
+    pub fn record_tick(&mut self) {
+        self.ticks += 1;
+        self.update_rates();
+    }
+
+    /// Lists an event other than a tick or frame, by the name of its kind
+    pub fn record_event(&mut self, kind: &'static str) {
+        if self.recent_events.len() == RECENT_EVENTS {
+            self.recent_events.pop_front();
+        }
+        self.recent_events.push_back(kind);
+        self.update_rates();
+    }
+
+    pub fn record_frame(&mut self, elapsed: Duration) {
+        if self.frame_times.len() == FRAME_SAMPLES {
+            self.frame_times.pop_front();
+        }
+        self.frame_times.push_back(elapsed);
+        self.frames += 1;
+        self.update_rates();
+    }
+
+    /// Recomputes frames and ticks per second about once a second
+    fn update_rates(&mut self) {
+        let elapsed = self.window_start.elapsed();
+        if elapsed >= Duration::from_secs(1) {
+            self.fps = self.frames as f64 / elapsed.as_secs_f64();
+            self.tps = self.ticks as f64 / elapsed.as_secs_f64();
+            self.frames = 0;
+            self.ticks = 0;
+            self.window_start = Instant::now();
+        }
+    }
+
+    /// Draws the overlay in the top right corner, comparing the measured rates
+    /// with the configured ones
+    pub fn draw(
+        &self,
+        area: Rect,
+        frame: &mut Frame<'_>,
+        queue_depth: usize,
+        frame_rate: f64,
+        tick_rate: f64,
+    ) {
+        if !self.is_visible() {
+            return;
+        }
+
+        let mut sorted: Vec<Duration> = self.frame_times.iter().copied().collect();
+        sorted.sort();
+
+        let mut lines = vec![
+            Line::from(format!("fps  {:>6.1} / {:.1}", self.fps, frame_rate)),
+            Line::from(format!("tps  {:>6.1} / {:.1}", self.tps, tick_rate)),
+            Line::from(format!(
+                "draw p50 {:.2}ms p95 {:.2}ms p99 {:.2}ms",
+                percentile_ms(&sorted, 0.50),
+                percentile_ms(&sorted, 0.95),
+                percentile_ms(&sorted, 0.99),
+            )),
+            Line::from(format!("queue {queue_depth}")),
+            Line::from("recent events:"),
+        ];
+        lines.extend(
+            self.recent_events
+                .iter()
+                .rev()
+                .map(|event| Line::from(format!("  {event}"))),
+        );
+
+        let width = 46.min(area.width);
+        let height = (lines.len() as u16 + 2).min(area.height);
+        let area = Rect::new(area.right() - width, area.y, width, height);
+
+        frame.render_widget(Clear, area);
+        frame.render_widget(
+            Paragraph::new(lines).block(Block::bordered().title("Debug (F12)")),
+            area,
+        );
+    }
+}
+
+/// Value at `percentile` (0.0..=1.0) of already sorted durations, in milliseconds
+fn percentile_ms(sorted: &[Duration], percentile: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let index = ((sorted.len() - 1) as f64 * percentile).round() as usize;
+    sorted[index].as_secs_f64() * 1000.0
+}
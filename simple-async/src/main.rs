@@ -1,12 +1,29 @@
 mod app;
+mod backend;
+mod debug_overlay;
 mod error;
 
+use std::process::ExitCode;
+
 use app::App;
-use error::Error;
+use error::{Context, Error};
 
 type Result<T> = std::result::Result<T, Error>;
 
 #[tokio::main]
-async fn main() -> Result<()> {
-    App::new(60f64, 10f64)?.run().await
+async fn main() -> ExitCode {
+    match run().await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{}", err.report());
+            err.exit_code()
+        }
+    }
+}
+
+async fn run() -> Result<()> {
+    App::new(60f64, 10f64)
+        .context("starting the app")?
+        .run()
+        .await
 }
@@ -1,4 +1,6 @@
-use clap::Parser;
+use std::{net::SocketAddr, path::PathBuf};
+
+use clap::{Parser, Subcommand};
 
 #[derive(Parser)]
 #[command(version, about, long_about = None)]
@@ -7,4 +9,80 @@ pub struct Cli {
     pub frame: f64,
     #[arg(short, long, default_value_t = 10f64)]
     pub tick: f64,
+    /// Draw in HEIGHT lines below the prompt instead of the whole screen,
+    /// leaving the output in the scrollback
+    #[arg(short, long, value_name = "HEIGHT")]
+    pub inline: Option<u16>,
+    /// Write every key press and resize with its time to FILE
+    #[arg(long, value_name = "FILE", conflicts_with = "replay")]
+    pub record: Option<PathBuf>,
+    /// Feed the events recorded in FILE to the app, live input still works
+    #[arg(long, value_name = "FILE")]
+    pub replay: Option<PathBuf>,
+    /// How many times faster than recorded to replay
+    #[arg(long, default_value_t = 1f64, requires = "replay")]
+    pub speed: f64,
+    /// Save everything drawn as an asciicast v2 file, to share with
+    /// `asciinema play`
+    #[arg(long, value_name = "FILE")]
+    pub cast: Option<PathBuf>,
+    /// Accept commands from `send` on a Unix socket
+    #[arg(long)]
+    pub control: bool,
+    /// Socket for --control and `send`, `client.sock` in the runtime
+    /// directory by default, required without one
+    #[arg(long, value_name = "PATH", global = true)]
+    pub socket: Option<PathBuf>,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Send a request to an instance running with --control and print the
+    /// result
+    Send {
+        /// One of switch_tab, refresh, get_state, press, print or quit
+        method: String,
+        /// Parameters as a JSON object, e.g. '{"tab":"Two"}'
+        params: Option<String>,
+    },
+}
+
+/// Arguments of the SSH server, see the `ssh` crate
+#[derive(Parser)]
+#[command(version, about = "Serve the client over SSH", long_about = None)]
+pub struct SshCli {
+    /// Address to accept SSH connections on
+    #[arg(short, long, default_value = "127.0.0.1:2222")]
+    pub listen: SocketAddr,
+    /// OpenSSH private key to use as host key, otherwise a new one is
+    /// generated on every start
+    #[arg(long, value_name = "FILE")]
+    pub host_key: Option<PathBuf>,
+    /// OpenSSH authorized_keys file with the keys allowed in,
+    /// `~/.ssh/authorized_keys` by default
+    #[arg(long, value_name = "FILE")]
+    pub authorized_keys: Option<PathBuf>,
+    /// Let in anyone who can reach the port, without a key
+    #[arg(long)]
+    pub allow_anonymous: bool,
+    #[arg(short, long, default_value_t = 60f64)]
+    pub frame: f64,
+    #[arg(short, long, default_value_t = 10f64)]
+    pub tick: f64,
+}
+
+/// Arguments of the browser server, see the `web` crate
+#[derive(Parser)]
+#[command(version, about = "Serve the client to a browser over a WebSocket", long_about = None)]
+pub struct WebCli {
+    /// Address to serve the page and WebSocket on, keep it on localhost as
+    /// there is no authentication
+    #[arg(short, long, default_value = "127.0.0.1:8080")]
+    pub listen: SocketAddr,
+    #[arg(short, long, default_value_t = 60f64)]
+    pub frame: f64,
+    #[arg(short, long, default_value_t = 10f64)]
+    pub tick: f64,
 }
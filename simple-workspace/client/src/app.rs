@@ -1,13 +1,32 @@
-use std::{io::Stdout, time::Duration};
+use std::{
+    io,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
 
-use crossterm::event::{Event as CrosstermEvent, EventStream, KeyCode, KeyEventKind};
-use futures::{FutureExt, StreamExt};
-use ratatui::{prelude::CrosstermBackend, Terminal};
+use ratatui::{
+    backend::{Backend as RatatuiBackend, TestBackend},
+    layout::{Position, Rect},
+    widgets::{Paragraph, Widget, Wrap},
+    Terminal, TerminalOptions, Viewport,
+};
+use serde_json::{json, Value};
 use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
 
+#[cfg(unix)]
+use crate::control::ControlServer;
+#[cfg(feature = "crossterm")]
+use crate::remote::{self, RemoteBackend, RemoteTerminal};
 use crate::{
+    backend::{self, Backend, EventSource, KeyCode},
+    cast::Cast,
+    control::{ControlCommand, ControlRequest},
+    debug_overlay::DebugOverlay,
+    record::{self, Recorder},
     screen_manager::{Renderable, ScreenManager},
-    Error, Result,
+    state::{self, UiState},
+    toast::Toast,
+    Context, Error, Result,
 };
 
 #[derive(Debug)]
@@ -15,41 +34,328 @@ pub enum Event {
     Key(KeyCode),
     Tick,
     Render,
+    /// New size of the terminal
+    Resize(Rect),
+    /// Text pasted into the terminal
+    Paste(String),
+    /// Line printed above the inline viewport, into the scrollback, or shown
+    /// for a moment when drawing on the whole screen
+    Print(String),
+    /// Request from another program, see [`crate::control`]
+    Control(ControlRequest),
 }
 
-#[derive(Debug)]
-pub struct App {
+impl Event {
+    /// Name of the variant, to show events without the text they carry
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Event::Key(_) => "key",
+            Event::Tick => "tick",
+            Event::Render => "render",
+            Event::Resize(_) => "resize",
+            Event::Paste(_) => "paste",
+            Event::Print(_) => "print",
+            Event::Control(_) => "control",
+        }
+    }
+}
+
+/// Input from the terminal the app draws on
+pub(crate) enum Input {
+    Key(KeyCode),
+    Resize(Rect),
+    Paste(String),
+}
+
+impl From<Input> for Event {
+    fn from(input: Input) -> Self {
+        match input {
+            Input::Key(key) => Event::Key(key),
+            Input::Resize(area) => Event::Resize(area),
+            Input::Paste(text) => Event::Paste(text),
+        }
+    }
+}
+
+enum InputSource {
+    Local(EventSource),
+    /// Remote terminals, or a recording when headless
+    Channel(UnboundedReceiver<Input>),
+}
+
+impl InputSource {
+    /// Waits for the next input, `None` once input is closed
+    async fn next(&mut self) -> Option<io::Result<Input>> {
+        match self {
+            InputSource::Local(events) => events.next().await,
+            InputSource::Channel(input) => input.recv().await.map(Ok),
+        }
+    }
+}
+
+/// The UI drawn on a terminal, the local one by default
+pub struct App<B: RatatuiBackend = Backend> {
     should_quit: bool,
-    crossterm_event: EventStream,
+    input: InputSource,
     frame_rate: f64,
     tick_rate: f64,
     event_rx: UnboundedReceiver<Event>,
     event_tx: UnboundedSender<Event>,
     screen_manager: ScreenManager,
-    terminal: Terminal<CrosstermBackend<Stdout>>,
+    terminal: Terminal<B>,
+    debug_overlay: DebugOverlay,
+    /// Printed lines when there is no scrollback to print them to
+    toast: Toast,
+    /// Drawing below the prompt instead of on the alternate screen
+    inline: bool,
+    /// Where the UI state is saved on quit, remote sessions don't keep any
+    state_path: Option<PathBuf>,
+    recorder: Option<Recorder>,
+    /// Told about resizes, the output reaches it through the backend
+    cast: Option<Cast>,
+    /// Removes the socket when the app is dropped
+    #[cfg(unix)]
+    control: Option<ControlServer>,
 }
 
 impl App {
-    pub fn new(frame_rate: f64, tick_rate: f64) -> Result<Self> {
+    pub fn new(frame_rate: f64, tick_rate: f64, viewport: Viewport) -> Result<Self> {
+        let inline = matches!(viewport, Viewport::Inline(_));
+        let terminal = backend::terminal(viewport).context("creating the terminal")?;
+        // Created after the terminal, which may read the cursor position from stdin
+        let input = EventSource::new().context("reading terminal input")?;
+
+        let mut app = App::with_terminal(
+            frame_rate,
+            tick_rate,
+            terminal,
+            InputSource::Local(input),
+            inline,
+        );
+
+        let state_path = state::state_path();
+        app.restore_state(&state::load(&state_path));
+        app.state_path = Some(state_path);
+
+        Ok(app)
+    }
+
+    /// Copies everything written to the terminal into an asciicast at
+    /// `path`, see [`crate::cast`]
+    pub fn cast_to(&mut self, path: &Path) -> Result<()> {
+        let size = self.terminal.size().context("reading the terminal size")?;
+        let cast = Cast::create(path, size.width, size.height)
+            .with_context(|| format!("creating the cast {}", path.display()))?;
+        backend::tee(&mut self.terminal, cast.clone()).context("teeing the terminal output")?;
+        self.cast = Some(cast);
+        Ok(())
+    }
+
+    /// Accepts requests from other programs on the Unix socket at `path`,
+    /// see [`crate::control`]
+    #[cfg(unix)]
+    pub fn serve_control(&mut self, path: &Path) -> Result<()> {
+        let server = ControlServer::bind(path, self.event_tx.clone())
+            .with_context(|| format!("listening on {}", path.display()))?;
+        self.control = Some(server);
+        Ok(())
+    }
+
+    /// Control requests need Unix sockets
+    #[cfg(not(unix))]
+    pub fn serve_control(&mut self, _path: &Path) -> Result<()> {
+        Err(io::Error::from(io::ErrorKind::Unsupported)).context("serving control requests")
+    }
+
+    /// Runs the UI until quit, restoring the terminal even if it fails
+    pub async fn run(&mut self) -> Result<()> {
+        let mut result =
+            backend::startup(&mut self.terminal, self.inline).context("setting up the terminal");
+
+        if result.is_ok() {
+            result = self.event_loop().await;
+        }
+
+        if result.is_ok() {
+            result = self.save_state().context("saving the UI state");
+        }
+
+        if self.inline {
+            // Leave the last frame in the scrollback and put the prompt below it
+            let bottom = self.terminal.get_frame().area().bottom();
+            let moved = self
+                .terminal
+                .set_cursor_position(Position::new(0, bottom.saturating_sub(1)))
+                .context("moving the cursor below the viewport");
+            result = result.and(moved);
+        }
+
+        let restored =
+            backend::shutdown(&mut self.terminal, self.inline).context("restoring the terminal");
+        if restored.is_ok() && self.inline {
+            println!();
+        }
+        result.and(restored)
+    }
+}
+
+#[cfg(feature = "crossterm")]
+impl App<RemoteBackend> {
+    /// App for a remote terminal of `width` x `height`, see [`crate::remote`]
+    pub fn remote(
+        frame_rate: f64,
+        tick_rate: f64,
+        width: u16,
+        height: u16,
+    ) -> Result<(Self, RemoteTerminal)> {
+        let (terminal, input, remote) =
+            remote::session(width, height).context("creating the remote terminal")?;
+        let app = App::with_terminal(
+            frame_rate,
+            tick_rate,
+            terminal,
+            InputSource::Channel(input),
+            false,
+        );
+        Ok((app, remote))
+    }
+
+    /// Runs the UI until quit or until the [`RemoteTerminal`] is dropped
+    pub async fn run_remote(&mut self) -> Result<()> {
+        let mut result = remote::startup(&mut self.terminal).context("setting up the terminal");
+
+        if result.is_ok() {
+            result = match self.event_loop().await {
+                // The connection closing ends the session
+                Err(Error::Input) => Ok(()),
+                result => result,
+            };
+        }
+
+        result.and(remote::shutdown(&mut self.terminal).context("restoring the terminal"))
+    }
+}
+
+impl App<TestBackend> {
+    /// App drawing into a [`TestBackend`] of `width` x `height` with the
+    /// recording at `path` as its only input, played `speed` times as fast
+    ///
+    /// Run it with [`App::run_headless`] to see where a session leads without
+    /// a terminal, e.g. in tests. The backend keeps its size, recorded resizes
+    /// only reach the app as events.
+    pub fn headless(
+        frame_rate: f64,
+        tick_rate: f64,
+        width: u16,
+        height: u16,
+        path: &Path,
+        speed: f64,
+    ) -> Result<Self> {
+        let terminal = Terminal::with_options(
+            TestBackend::new(width, height),
+            TerminalOptions {
+                viewport: Viewport::Fullscreen,
+            },
+        )
+        .context("creating the terminal")?;
+        let (input_tx, input) = unbounded_channel();
+        record::replay(path, speed, input_tx)
+            .with_context(|| format!("replaying {}", path.display()))?;
+
+        Ok(App::with_terminal(
+            frame_rate,
+            tick_rate,
+            terminal,
+            InputSource::Channel(input),
+            false,
+        ))
+    }
+
+    /// Runs the UI until quit or until the recording ended, then draws the
+    /// final frame
+    pub async fn run_headless(&mut self) -> Result<()> {
+        match self.event_loop().await {
+            Ok(()) | Err(Error::Input) => {}
+            Err(err) => return Err(err),
+        }
+
+        // Handle what the last of the input left queued
+        while !self.should_quit {
+            let Ok(event) = self.event_rx.try_recv() else {
+                break;
+            };
+            self.handle_event(event)?;
+        }
+        self.handle_event(Event::Render)
+    }
+
+    pub fn backend(&self) -> &TestBackend {
+        self.terminal.backend()
+    }
+}
+
+impl<B: RatatuiBackend> App<B> {
+    fn with_terminal(
+        frame_rate: f64,
+        tick_rate: f64,
+        terminal: Terminal<B>,
+        input: InputSource,
+        inline: bool,
+    ) -> Self {
         let (event_tx, event_rx) = unbounded_channel();
-        let crossterm_event = EventStream::new();
-        let terminal = Terminal::new(CrosstermBackend::new(std::io::stdout()))?;
 
-        Ok(Self {
+        Self {
             should_quit: false,
+            screen_manager: ScreenManager::new(event_tx.clone()),
             event_rx,
             event_tx,
             frame_rate,
             tick_rate,
-            crossterm_event,
+            input,
             terminal,
-            screen_manager: ScreenManager::new(),
-        })
+            debug_overlay: DebugOverlay::new(),
+            toast: Toast::default(),
+            inline,
+            state_path: None,
+            recorder: None,
+            cast: None,
+            #[cfg(unix)]
+            control: None,
+        }
     }
 
-    pub async fn run(&mut self) -> Result<()> {
-        startup()?;
+    /// Writes every key press and resize to `path`, see [`crate::record`]
+    pub fn record_to(&mut self, path: &Path) -> Result<()> {
+        let recorder = Recorder::create(path)
+            .with_context(|| format!("creating the recording {}", path.display()))?;
+        self.recorder = Some(recorder);
+        Ok(())
+    }
+
+    /// Feeds the events recorded in `path` to the app, `speed` times as fast
+    /// as they happened
+    pub fn replay_from(&mut self, path: &Path, speed: f64) -> Result<()> {
+        record::replay(path, speed, self.event_tx.clone())
+            .with_context(|| format!("replaying {}", path.display()))
+    }
+
+    fn restore_state(&mut self, state: &UiState) {
+        self.screen_manager.set_current_tab(state.tab);
+        self.debug_overlay.set_visible(state.debug_overlay);
+    }
+
+    fn save_state(&self) -> io::Result<()> {
+        let Some(path) = &self.state_path else {
+            return Ok(());
+        };
+        let state = UiState {
+            tab: self.screen_manager.current_tab(),
+            debug_overlay: self.debug_overlay.is_visible(),
+        };
+        state::save(path, &state)
+    }
 
+    async fn event_loop(&mut self) -> Result<()> {
         let frame_rate = Duration::from_secs_f64(1.0 / self.frame_rate);
         let tick_rate = Duration::from_secs_f64(1.0 / self.tick_rate);
         let mut frame_interval = tokio::time::interval(frame_rate);
@@ -59,55 +365,143 @@ impl App {
             tokio::select! {
 
                 _tick = tick_interval.tick() => {
-                    self.event_tx.send(Event::Tick).unwrap();
+                    self.event_tx.send(Event::Tick)?;
                 }
 
                 _frame = frame_interval.tick() => {
-                    self.event_tx.send(Event::Render).unwrap();
+                    self.event_tx.send(Event::Render)?;
                 }
 
                 Some(event) = self.event_rx.recv() => {
-                    match event {
-                        Event::Render => {
-                            self.terminal.draw(|frame| {
-                                self.screen_manager.draw(frame.area(),frame);
-                            })?;
-                        }
-                        Event::Key(key) => {
-                            if let Ok(Some(true)) = self.screen_manager.handle_key(&key) {
-                                self.should_quit=true
-                            }
-                        }
-                        _ => {}
-                    }
+                    self.handle_event(event)?;
                 }
 
-                event = self.crossterm_event.next().fuse() => {
-                    match event.ok_or(Error::Crossterm)?? {
-                        CrosstermEvent::Key(key) => {
-                            if let KeyEventKind::Press = key.kind {
-                                self.event_tx.send(Event::Key(key.code))?
-                            }
-                        }
-                        _ => {}
-                    }
+                input = self.input.next() => {
+                    let input = input
+                        .ok_or(Error::Input)?
+                        .context("reading terminal input")?;
+                    self.event_tx.send(input.into())?;
                 }
             }
         }
 
-        shutdown()?;
         Ok(())
     }
-}
 
-fn startup() -> Result<()> {
-    crossterm::terminal::enable_raw_mode()?;
-    crossterm::execute!(std::io::stdout(), crossterm::terminal::EnterAlternateScreen)?;
-    Ok(())
-}
+    fn handle_event(&mut self, event: Event) -> Result<()> {
+        match &event {
+            Event::Tick => self.debug_overlay.record_tick(),
+            // Frames are counted once they have been drawn
+            Event::Render => {}
+            event => self.debug_overlay.record_event(event.kind()),
+        }
+        if let Some(recorder) = &mut self.recorder {
+            recorder.record(&event).context("recording an event")?;
+        }
 
-fn shutdown() -> Result<()> {
-    crossterm::terminal::disable_raw_mode()?;
-    crossterm::execute!(std::io::stdout(), crossterm::terminal::LeaveAlternateScreen)?;
-    Ok(())
+        match event {
+            Event::Render => {
+                if let Some(cast) = &self.cast {
+                    let size = self.terminal.size().context("reading the terminal size")?;
+                    cast.resize(size.width, size.height)
+                        .context("recording a resize")?;
+                }
+                let queue_depth = self.event_rx.len();
+                let started = Instant::now();
+                self.terminal
+                    .draw(|frame| {
+                        self.screen_manager.draw(frame.area(), frame);
+                        self.toast.draw(frame.area(), frame);
+                        self.debug_overlay.draw(
+                            frame.area(),
+                            frame,
+                            queue_depth,
+                            self.frame_rate,
+                            self.tick_rate,
+                        );
+                    })
+                    .context("drawing a frame")?;
+                self.debug_overlay.record_frame(started.elapsed());
+            }
+            Event::Print(line) => self.print(line)?,
+            Event::Resize(area) => self
+                .terminal
+                .resize(area)
+                .context("resizing the terminal")?,
+            Event::Paste(text) => self.screen_manager.handle_paste(&text)?,
+            Event::Control(request) => self.control(request)?,
+            Event::Key(KeyCode::F(12)) => self.debug_overlay.toggle(),
+            Event::Key(key) => {
+                if let Some(true) = self.screen_manager.handle_key(&key)? {
+                    self.should_quit = true
+                }
+            }
+            Event::Tick => {}
+        }
+        Ok(())
+    }
+
+    /// Carries out a control request and answers it
+    fn control(&mut self, request: ControlRequest) -> Result<()> {
+        let result = match &request.command {
+            ControlCommand::SwitchTab { tab } => {
+                self.screen_manager.set_current_tab(*tab);
+                Ok(Value::Null)
+            }
+            ControlCommand::Refresh => match self.terminal.clear() {
+                Ok(()) => {
+                    self.event_tx.send(Event::Render)?;
+                    Ok(Value::Null)
+                }
+                Err(err) => Err(format!("clearing the terminal: {err}")),
+            },
+            ControlCommand::GetState => {
+                let area = self.terminal.get_frame().area();
+                Ok(json!({
+                    "tab": self.screen_manager.current_tab(),
+                    "debug_overlay": self.debug_overlay.is_visible(),
+                    "inline": self.inline,
+                    "width": area.width,
+                    "height": area.height,
+                }))
+            }
+            ControlCommand::Press { key } => {
+                self.event_tx.send(Event::Key(*key))?;
+                Ok(Value::Null)
+            }
+            ControlCommand::Print { line } => {
+                self.event_tx.send(Event::Print(line.clone()))?;
+                Ok(Value::Null)
+            }
+            ControlCommand::Quit => {
+                self.should_quit = true;
+                Ok(Value::Null)
+            }
+        };
+        request.respond(result);
+        Ok(())
+    }
+
+    /// Prints `line` above the inline viewport, where it scrolls away with the
+    /// rest of the terminal output
+    ///
+    /// The alternate screen has no scrollback, so there it is shown as a
+    /// toast instead.
+    fn print(&mut self, line: String) -> Result<()> {
+        if !self.inline {
+            self.toast.show(line);
+            return Ok(());
+        }
+
+        let width = self
+            .terminal
+            .size()
+            .context("reading the terminal size")?
+            .width;
+        let paragraph = Paragraph::new(line).wrap(Wrap { trim: false });
+        let height = paragraph.line_count(width).max(1) as u16;
+        self.terminal
+            .insert_before(height, |buf| paragraph.render(buf.area, buf))
+            .context("printing above the viewport")
+    }
 }
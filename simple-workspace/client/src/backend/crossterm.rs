@@ -0,0 +1,110 @@
+use std::io::{self, Stdout};
+
+use crossterm::event::{
+    DisableBracketedPaste, EnableBracketedPaste, Event, EventStream, KeyCode as CrosstermKeyCode,
+    KeyEventKind,
+};
+use futures::StreamExt;
+use ratatui::{layout::Rect, prelude::CrosstermBackend, Terminal, TerminalOptions, Viewport};
+
+use super::KeyCode;
+use crate::{
+    app::Input,
+    cast::{Cast, Tee},
+};
+
+pub type Backend = CrosstermBackend<Tee<Stdout>>;
+
+pub fn terminal(viewport: Viewport) -> io::Result<Terminal<Backend>> {
+    Terminal::with_options(
+        CrosstermBackend::new(Tee::new(io::stdout())),
+        TerminalOptions { viewport },
+    )
+}
+
+pub fn tee(terminal: &mut Terminal<Backend>, cast: Cast) -> io::Result<()> {
+    terminal.backend_mut().writer_mut().set_cast(cast);
+    Ok(())
+}
+
+pub fn startup(terminal: &mut Terminal<Backend>, inline: bool) -> io::Result<()> {
+    crossterm::terminal::enable_raw_mode()?;
+    crossterm::execute!(terminal.backend_mut(), EnableBracketedPaste)?;
+    if !inline {
+        crossterm::execute!(
+            terminal.backend_mut(),
+            crossterm::terminal::EnterAlternateScreen
+        )?;
+    }
+    Ok(())
+}
+
+pub fn shutdown(terminal: &mut Terminal<Backend>, inline: bool) -> io::Result<()> {
+    crossterm::terminal::disable_raw_mode()?;
+    crossterm::execute!(terminal.backend_mut(), DisableBracketedPaste)?;
+    if !inline {
+        crossterm::execute!(
+            terminal.backend_mut(),
+            crossterm::terminal::LeaveAlternateScreen
+        )?;
+    }
+    Ok(())
+}
+
+pub struct EventSource {
+    events: EventStream,
+}
+
+impl EventSource {
+    pub fn new() -> io::Result<Self> {
+        Ok(Self {
+            events: EventStream::new(),
+        })
+    }
+
+    /// Waits for the next key press, resize or paste, `None` once input is
+    /// closed
+    pub async fn next(&mut self) -> Option<io::Result<Input>> {
+        loop {
+            let event = match self.events.next().await? {
+                Ok(event) => event,
+                Err(err) => return Some(Err(err)),
+            };
+            let input = match event {
+                Event::Key(key) if key.kind == KeyEventKind::Press => {
+                    convert_key(key.code).map(Input::Key)
+                }
+                Event::Resize(width, height) => Some(Input::Resize(Rect::new(0, 0, width, height))),
+                Event::Paste(text) => Some(Input::Paste(text)),
+                _ => None,
+            };
+            if let Some(input) = input {
+                return Some(Ok(input));
+            }
+        }
+    }
+}
+
+fn convert_key(key: CrosstermKeyCode) -> Option<KeyCode> {
+    let key = match key {
+        CrosstermKeyCode::Char(c) => KeyCode::Char(c),
+        CrosstermKeyCode::F(n) => KeyCode::F(n),
+        CrosstermKeyCode::Enter => KeyCode::Enter,
+        CrosstermKeyCode::Esc => KeyCode::Esc,
+        CrosstermKeyCode::Tab => KeyCode::Tab,
+        CrosstermKeyCode::BackTab => KeyCode::BackTab,
+        CrosstermKeyCode::Backspace => KeyCode::Backspace,
+        CrosstermKeyCode::Delete => KeyCode::Delete,
+        CrosstermKeyCode::Insert => KeyCode::Insert,
+        CrosstermKeyCode::Up => KeyCode::Up,
+        CrosstermKeyCode::Down => KeyCode::Down,
+        CrosstermKeyCode::Left => KeyCode::Left,
+        CrosstermKeyCode::Right => KeyCode::Right,
+        CrosstermKeyCode::Home => KeyCode::Home,
+        CrosstermKeyCode::End => KeyCode::End,
+        CrosstermKeyCode::PageUp => KeyCode::PageUp,
+        CrosstermKeyCode::PageDown => KeyCode::PageDown,
+        _ => return None,
+    };
+    Some(key)
+}
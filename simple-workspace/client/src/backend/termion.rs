@@ -0,0 +1,115 @@
+use std::{
+    io::{self, Stdout, Write},
+    thread,
+};
+
+use ratatui::{prelude::TermionBackend, Terminal, TerminalOptions, Viewport};
+use termion::{
+    event::Key,
+    input::TermRead,
+    raw::{IntoRawMode, RawTerminal},
+    screen::{ToAlternateScreen, ToMainScreen},
+};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver};
+
+use super::KeyCode;
+use crate::{
+    app::Input,
+    cast::{Cast, Tee},
+};
+
+pub type Backend = TermionBackend<RawTerminal<Tee<Stdout>>>;
+
+pub fn terminal(viewport: Viewport) -> io::Result<Terminal<Backend>> {
+    // An inline viewport reads the cursor position, which needs raw mode,
+    // otherwise raw mode is only switched on in `startup`
+    let stdout = Tee::new(io::stdout()).into_raw_mode()?;
+    let mut terminal =
+        Terminal::with_options(TermionBackend::new(stdout), TerminalOptions { viewport })?;
+    terminal.backend_mut().writer_mut().suspend_raw_mode()?;
+    Ok(terminal)
+}
+
+pub fn tee(terminal: &mut Terminal<Backend>, cast: Cast) -> io::Result<()> {
+    terminal.backend_mut().writer_mut().set_cast(cast);
+    Ok(())
+}
+
+pub fn startup(terminal: &mut Terminal<Backend>, inline: bool) -> io::Result<()> {
+    let stdout = terminal.backend_mut().writer_mut();
+    stdout.activate_raw_mode()?;
+    if !inline {
+        write!(stdout, "{ToAlternateScreen}")?;
+        stdout.flush()?;
+    }
+    Ok(())
+}
+
+pub fn shutdown(terminal: &mut Terminal<Backend>, inline: bool) -> io::Result<()> {
+    let stdout = terminal.backend_mut().writer_mut();
+    if !inline {
+        write!(stdout, "{ToMainScreen}")?;
+        stdout.flush()?;
+    }
+    stdout.suspend_raw_mode()
+}
+
+/// Keys read from stdin on a separate thread, as termion only reads blocking
+pub struct EventSource {
+    keys: UnboundedReceiver<io::Result<KeyCode>>,
+}
+
+impl EventSource {
+    pub fn new() -> io::Result<Self> {
+        let (tx, keys) = unbounded_channel();
+        thread::Builder::new()
+            .name("termion-input".to_string())
+            .spawn(move || {
+                for key in io::stdin().keys() {
+                    let key = match key {
+                        Ok(key) => match convert_key(key) {
+                            Some(key) => Ok(key),
+                            None => continue,
+                        },
+                        Err(err) => Err(err),
+                    };
+                    if tx.send(key).is_err() {
+                        break;
+                    }
+                }
+            })?;
+        Ok(Self { keys })
+    }
+
+    /// Waits for the next key press, `None` once input is closed
+    ///
+    /// termion reports neither resizes, which are noticed on the next draw,
+    /// nor pastes, which arrive as keys.
+    pub async fn next(&mut self) -> Option<io::Result<Input>> {
+        Some(self.keys.recv().await?.map(Input::Key))
+    }
+}
+
+fn convert_key(key: Key) -> Option<KeyCode> {
+    let key = match key {
+        Key::Char('\n') => KeyCode::Enter,
+        Key::Char('\t') => KeyCode::Tab,
+        Key::Char(c) => KeyCode::Char(c),
+        Key::F(n) => KeyCode::F(n),
+        Key::Esc => KeyCode::Esc,
+        Key::BackTab => KeyCode::BackTab,
+        Key::Backspace => KeyCode::Backspace,
+        Key::Delete => KeyCode::Delete,
+        Key::Insert => KeyCode::Insert,
+        Key::Up => KeyCode::Up,
+        Key::Down => KeyCode::Down,
+        Key::Left => KeyCode::Left,
+        Key::Right => KeyCode::Right,
+        Key::Home => KeyCode::Home,
+        Key::End => KeyCode::End,
+        Key::PageUp => KeyCode::PageUp,
+        Key::PageDown => KeyCode::PageDown,
+        _ => return None,
+    };
+    Some(key)
+}
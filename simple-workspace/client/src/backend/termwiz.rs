@@ -0,0 +1,123 @@
+use std::{io, thread};
+
+use ratatui::{layout::Rect, prelude::TermwizBackend, Terminal, TerminalOptions, Viewport};
+use termwiz::{
+    caps::Capabilities,
+    input::{InputEvent, KeyCode as TermwizKeyCode},
+    surface::Change,
+    terminal::{buffered::BufferedTerminal, SystemTerminal, Terminal as _},
+};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver};
+
+use super::KeyCode;
+use crate::{app::Input, cast::Cast};
+
+pub type Backend = TermwizBackend;
+
+pub fn terminal(viewport: Viewport) -> io::Result<Terminal<Backend>> {
+    // `TermwizBackend::new` would enter raw mode and the alternate screen
+    // right away, leave that to `startup`
+    let caps = Capabilities::new_from_env().map_err(io::Error::other)?;
+    let system = SystemTerminal::new_from_stdio(caps).map_err(io::Error::other)?;
+    let buffered = BufferedTerminal::new(system).map_err(io::Error::other)?;
+    Terminal::with_options(
+        TermwizBackend::with_buffered_terminal(buffered),
+        TerminalOptions { viewport },
+    )
+}
+
+/// Termwiz writes to the tty itself, so there is no output to tee
+pub fn tee(_terminal: &mut Terminal<Backend>, _cast: Cast) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "recording a cast needs the crossterm or termion backend",
+    ))
+}
+
+pub fn startup(terminal: &mut Terminal<Backend>, inline: bool) -> io::Result<()> {
+    let system = terminal.backend_mut().buffered_terminal_mut().terminal();
+    system.set_raw_mode().map_err(io::Error::other)?;
+    if !inline {
+        system.enter_alternate_screen().map_err(io::Error::other)?;
+    }
+    Ok(())
+}
+
+pub fn shutdown(terminal: &mut Terminal<Backend>, inline: bool) -> io::Result<()> {
+    let buffered = terminal.backend_mut().buffered_terminal_mut();
+    buffered.add_change(Change::CursorVisibility(
+        termwiz::surface::CursorVisibility::Visible,
+    ));
+    buffered.flush().map_err(io::Error::other)?;
+    let system = buffered.terminal();
+    if !inline {
+        system.exit_alternate_screen().map_err(io::Error::other)?;
+    }
+    system.set_cooked_mode().map_err(io::Error::other)
+}
+
+/// Keys polled on a separate thread from a second handle to the terminal, so
+/// the one drawing never blocks on input
+pub struct EventSource {
+    inputs: UnboundedReceiver<io::Result<Input>>,
+}
+
+impl EventSource {
+    pub fn new() -> io::Result<Self> {
+        let caps = Capabilities::new_from_env().map_err(io::Error::other)?;
+        let mut input = SystemTerminal::new_from_stdio(caps).map_err(io::Error::other)?;
+        let (tx, inputs) = unbounded_channel();
+        thread::Builder::new()
+            .name("termwiz-input".to_string())
+            .spawn(move || loop {
+                let input = match input.poll_input(None) {
+                    Ok(Some(InputEvent::Key(key))) => match convert_key(key.key) {
+                        Some(key) => Ok(Input::Key(key)),
+                        None => continue,
+                    },
+                    Ok(Some(InputEvent::Resized { cols, rows })) => Ok(Input::Resize(Rect::new(
+                        0,
+                        0,
+                        cols.try_into().unwrap_or(u16::MAX),
+                        rows.try_into().unwrap_or(u16::MAX),
+                    ))),
+                    Ok(Some(InputEvent::Paste(text))) => Ok(Input::Paste(text)),
+                    Ok(_) => continue,
+                    Err(err) => Err(io::Error::other(err)),
+                };
+                if tx.send(input).is_err() {
+                    break;
+                }
+            })?;
+        Ok(Self { inputs })
+    }
+
+    /// Waits for the next key press, resize or paste, `None` once input is
+    /// closed
+    pub async fn next(&mut self) -> Option<io::Result<Input>> {
+        self.inputs.recv().await
+    }
+}
+
+fn convert_key(key: TermwizKeyCode) -> Option<KeyCode> {
+    let key = match key {
+        TermwizKeyCode::Char(c) => KeyCode::Char(c),
+        TermwizKeyCode::Function(n) => KeyCode::F(n),
+        TermwizKeyCode::Enter => KeyCode::Enter,
+        TermwizKeyCode::Escape => KeyCode::Esc,
+        TermwizKeyCode::Tab => KeyCode::Tab,
+        TermwizKeyCode::Backspace => KeyCode::Backspace,
+        TermwizKeyCode::Delete => KeyCode::Delete,
+        TermwizKeyCode::Insert => KeyCode::Insert,
+        TermwizKeyCode::UpArrow => KeyCode::Up,
+        TermwizKeyCode::DownArrow => KeyCode::Down,
+        TermwizKeyCode::LeftArrow => KeyCode::Left,
+        TermwizKeyCode::RightArrow => KeyCode::Right,
+        TermwizKeyCode::Home => KeyCode::Home,
+        TermwizKeyCode::End => KeyCode::End,
+        TermwizKeyCode::PageUp => KeyCode::PageUp,
+        TermwizKeyCode::PageDown => KeyCode::PageDown,
+        _ => return None,
+    };
+    Some(key)
+}
@@ -0,0 +1,280 @@
+//! Writing what the app draws as an [asciicast v2] file, to play back with
+//! `asciinema play` or embed in a page
+//!
+//! The file starts with a header holding the terminal size, followed by one
+//! `[seconds, "o", text]` line for each flushed chunk of terminal output and
+//! a `[seconds, "r", "COLSxROWS"]` line whenever the terminal is resized.
+//!
+//! [asciicast v2]: https://docs.asciinema.org/manual/asciicast/v2/
+
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+use serde::Serialize;
+
+#[derive(Serialize)]
+struct Header {
+    version: u8,
+    width: u16,
+    height: u16,
+    timestamp: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    env: Option<Env>,
+}
+
+#[derive(Serialize)]
+struct Env {
+    #[serde(rename = "TERM")]
+    term: String,
+}
+
+struct CastFile {
+    file: BufWriter<File>,
+    started: Instant,
+    size: (u16, u16),
+    /// Start of a UTF-8 character split across two flushes
+    #[cfg(any(feature = "crossterm", feature = "termion"))]
+    partial: Vec<u8>,
+}
+
+/// An asciicast being written, shared by the terminal output and the app,
+/// which reports resizes
+#[derive(Clone)]
+pub struct Cast {
+    inner: Arc<Mutex<CastFile>>,
+}
+
+impl Cast {
+    /// Creates the file at `path` for a terminal of `width` x `height`
+    pub fn create(path: &Path, width: u16, height: u16) -> io::Result<Self> {
+        let mut file = BufWriter::new(File::create(path)?);
+        let header = Header {
+            version: 2,
+            width,
+            height,
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |since| since.as_secs()),
+            env: std::env::var("TERM").ok().map(|term| Env { term }),
+        };
+        serde_json::to_writer(&mut file, &header).map_err(io::Error::other)?;
+        file.write_all(b"\n")?;
+        file.flush()?;
+
+        Ok(Self {
+            inner: Arc::new(Mutex::new(CastFile {
+                file,
+                started: Instant::now(),
+                size: (width, height),
+                #[cfg(any(feature = "crossterm", feature = "termion"))]
+                partial: Vec::new(),
+            })),
+        })
+    }
+
+    /// Adds terminal output, keeping an incomplete character at the end for
+    /// the next call as the file holds text
+    #[cfg(any(feature = "crossterm", feature = "termion"))]
+    pub fn output(&self, data: &[u8]) -> io::Result<()> {
+        let mut cast = self.lock();
+        let mut bytes = std::mem::take(&mut cast.partial);
+        bytes.extend_from_slice(data);
+
+        let complete = match std::str::from_utf8(&bytes) {
+            Err(err) if err.error_len().is_none() => err.valid_up_to(),
+            _ => bytes.len(),
+        };
+        cast.partial = bytes.split_off(complete);
+        if bytes.is_empty() {
+            return Ok(());
+        }
+        cast.event("o", &String::from_utf8_lossy(&bytes))
+    }
+
+    /// Adds a resize event if the size differs from the last one
+    pub fn resize(&self, width: u16, height: u16) -> io::Result<()> {
+        let mut cast = self.lock();
+        if cast.size == (width, height) {
+            return Ok(());
+        }
+        cast.size = (width, height);
+        cast.event("r", &format!("{width}x{height}"))
+    }
+
+    fn lock(&self) -> MutexGuard<'_, CastFile> {
+        self.inner.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl CastFile {
+    /// Writes one event line, flushing so a crash keeps everything up to it
+    fn event(&mut self, kind: &str, data: &str) -> io::Result<()> {
+        let at = self.started.elapsed().as_secs_f64();
+        serde_json::to_writer(&mut self.file, &(at, kind, data)).map_err(io::Error::other)?;
+        self.file.write_all(b"\n")?;
+        self.file.flush()
+    }
+}
+
+/// Passes everything through to `W` and, once a [`Cast`] is attached, also
+/// hands each flushed chunk to it
+///
+/// Termwiz writes to the tty itself, so only the other backends use this.
+#[cfg(any(feature = "crossterm", feature = "termion"))]
+pub struct Tee<W> {
+    inner: W,
+    cast: Option<Cast>,
+    buffer: Vec<u8>,
+}
+
+#[cfg(any(feature = "crossterm", feature = "termion"))]
+impl<W> Tee<W> {
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            cast: None,
+            buffer: Vec::new(),
+        }
+    }
+
+    pub fn set_cast(&mut self, cast: Cast) {
+        self.cast = Some(cast);
+    }
+}
+
+#[cfg(any(feature = "crossterm", feature = "termion"))]
+impl<W: Write> Write for Tee<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        if self.cast.is_some() {
+            self.buffer.extend_from_slice(&buf[..written]);
+        }
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()?;
+        match &self.cast {
+            Some(cast) if !self.buffer.is_empty() => cast.output(&std::mem::take(&mut self.buffer)),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(all(unix, any(feature = "crossterm", feature = "termion")))]
+impl<W: std::os::fd::AsFd> std::os::fd::AsFd for Tee<W> {
+    fn as_fd(&self) -> std::os::fd::BorrowedFd<'_> {
+        self.inner.as_fd()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    use super::*;
+
+    fn lines(path: &Path) -> Vec<Value> {
+        std::fs::read_to_string(path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    /// Kind and data of the events after the header
+    fn events(path: &Path) -> Vec<(String, String)> {
+        lines(path)[1..]
+            .iter()
+            .map(|event| {
+                assert!(event[0].as_f64().unwrap() >= 0.0);
+                let kind = event[1].as_str().unwrap().to_string();
+                let data = event[2].as_str().unwrap().to_string();
+                (kind, data)
+            })
+            .collect()
+    }
+
+    fn event(kind: &str, data: &str) -> (String, String) {
+        (kind.to_string(), data.to_string())
+    }
+
+    #[test]
+    fn starts_with_a_header() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.cast");
+        Cast::create(&path, 80, 24).unwrap();
+
+        let lines = lines(&path);
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0]["version"], 2);
+        assert_eq!(lines[0]["width"], 80);
+        assert_eq!(lines[0]["height"], 24);
+        assert!(lines[0]["timestamp"].as_u64().unwrap() > 0);
+    }
+
+    #[test]
+    #[cfg(any(feature = "crossterm", feature = "termion"))]
+    fn keeps_characters_split_across_writes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.cast");
+        let cast = Cast::create(&path, 80, 24).unwrap();
+
+        let euro = "€".as_bytes();
+        cast.output(&[b"a", &euro[..1]].concat()).unwrap();
+        cast.output(&euro[1..2]).unwrap();
+        cast.output(&[&euro[2..], b"b"].concat()).unwrap();
+
+        assert_eq!(events(&path), [event("o", "a"), event("o", "€b")]);
+    }
+
+    #[test]
+    #[cfg(any(feature = "crossterm", feature = "termion"))]
+    fn replaces_invalid_bytes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.cast");
+        let cast = Cast::create(&path, 80, 24).unwrap();
+
+        cast.output(b"a\xffb").unwrap();
+
+        assert_eq!(events(&path), [event("o", "a\u{fffd}b")]);
+    }
+
+    #[test]
+    fn records_size_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.cast");
+        let cast = Cast::create(&path, 80, 24).unwrap();
+
+        cast.resize(80, 24).unwrap();
+        cast.resize(100, 30).unwrap();
+        cast.resize(100, 30).unwrap();
+        cast.resize(80, 24).unwrap();
+
+        assert_eq!(events(&path), [event("r", "100x30"), event("r", "80x24")]);
+    }
+
+    #[test]
+    #[cfg(any(feature = "crossterm", feature = "termion"))]
+    fn tee_hands_flushed_output_to_the_cast() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.cast");
+        let mut tee = Tee::new(Vec::new());
+
+        tee.write_all(b"before").unwrap();
+        tee.flush().unwrap();
+        tee.set_cast(Cast::create(&path, 80, 24).unwrap());
+        tee.write_all(b"one ").unwrap();
+        tee.write_all(b"two").unwrap();
+        tee.flush().unwrap();
+        tee.flush().unwrap();
+
+        assert_eq!(tee.inner, b"beforeone two");
+        assert_eq!(events(&path), [event("o", "one two")]);
+    }
+}
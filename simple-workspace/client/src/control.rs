@@ -0,0 +1,80 @@
+//! Driving a running app from other programs over a Unix socket
+//!
+//! Each connection sends JSON-RPC 2.0 requests, one per line, and gets one
+//! response line for each request with an `id`. Requests are passed to the
+//! app as [`Event::Control`] and answered once the event loop handled them.
+//!
+//! ```text
+//! > {"jsonrpc":"2.0","id":1,"method":"switch_tab","params":{"tab":"Two"}}
+//! < {"jsonrpc":"2.0","id":1,"result":null}
+//! ```
+//!
+//! Methods are `switch_tab` (`tab`), `refresh`, `get_state`, `press`
+//! (`key`, as in a recording), `print` (`line`) and `quit`.
+
+use std::path::PathBuf;
+
+use serde::Deserialize;
+use serde_json::Value;
+use tokio::sync::oneshot;
+
+#[cfg(unix)]
+pub use self::socket::{send, ControlServer};
+use crate::{backend::KeyCode, screen_manager::Tab};
+
+#[cfg(unix)]
+mod socket;
+
+/// What a control request asks the app to do
+#[derive(Debug, Deserialize)]
+#[serde(tag = "method", content = "params", rename_all = "snake_case")]
+pub enum ControlCommand {
+    SwitchTab {
+        tab: Tab,
+    },
+    /// Redraws the whole screen
+    Refresh,
+    GetState,
+    Press {
+        key: KeyCode,
+    },
+    Print {
+        line: String,
+    },
+    Quit,
+}
+
+#[cfg(unix)]
+impl ControlCommand {
+    const METHODS: [&'static str; 6] = [
+        "switch_tab",
+        "refresh",
+        "get_state",
+        "press",
+        "print",
+        "quit",
+    ];
+}
+
+/// A command waiting for the app's answer
+#[derive(Debug)]
+pub struct ControlRequest {
+    pub command: ControlCommand,
+    reply: oneshot::Sender<Result<Value, String>>,
+}
+
+impl ControlRequest {
+    /// Sends the result back to the connection that asked
+    pub fn respond(self, result: Result<Value, String>) {
+        // The client may have disconnected without waiting
+        let _ = self.reply.send(result);
+    }
+}
+
+/// Socket used when none is given, e.g. `$XDG_RUNTIME_DIR/client.sock`
+///
+/// `None` without a runtime directory, as a shared one like `/tmp` would let
+/// other users take the name first.
+pub fn default_socket_path() -> Option<PathBuf> {
+    dirs::runtime_dir().map(|dir| dir.join(concat!(env!("CARGO_PKG_NAME"), ".sock")))
+}
@@ -0,0 +1,383 @@
+use std::{
+    fs,
+    os::unix::fs::{DirBuilderExt, FileTypeExt, PermissionsExt},
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::{
+    io::{self, AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::{UnixListener, UnixStream},
+    sync::{mpsc::UnboundedSender, oneshot},
+    task::JoinHandle,
+};
+
+use super::{ControlCommand, ControlRequest};
+use crate::{app::Event, Context, Error, Result};
+
+/// JSON-RPC error codes
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+/// The app could not carry out a valid request
+const APP_ERROR: i64 = -32000;
+
+#[derive(Deserialize)]
+struct Request {
+    jsonrpc: String,
+    /// Absent for notifications, which get no response
+    #[serde(default)]
+    id: Option<Value>,
+    method: String,
+    #[serde(default)]
+    params: Option<Value>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Response {
+    jsonrpc: String,
+    id: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    result: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    error: Option<ResponseError>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ResponseError {
+    code: i64,
+    message: String,
+}
+
+impl Response {
+    fn new(id: Value, result: std::result::Result<Value, (i64, String)>) -> Self {
+        let (result, error) = match result {
+            Ok(result) => (Some(result), None),
+            Err((code, message)) => (None, Some(ResponseError { code, message })),
+        };
+        Self {
+            jsonrpc: "2.0".to_string(),
+            id,
+            result,
+            error,
+        }
+    }
+}
+
+/// Accepts control connections until dropped, then removes the socket
+pub struct ControlServer {
+    path: PathBuf,
+    task: JoinHandle<()>,
+}
+
+impl ControlServer {
+    /// Listens on `path`, replacing a socket left behind by an instance that
+    /// is no longer running
+    pub fn bind(path: &Path, event_tx: UnboundedSender<Event>) -> io::Result<Self> {
+        if let Ok(metadata) = fs::symlink_metadata(path) {
+            if !metadata.file_type().is_socket() {
+                return Err(io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    "a file that is not a socket is in the way",
+                ));
+            }
+            if std::os::unix::net::UnixStream::connect(path).is_ok() {
+                return Err(io::Error::new(
+                    io::ErrorKind::AddrInUse,
+                    "another instance is listening",
+                ));
+            }
+            fs::remove_file(path)?;
+        }
+
+        let listener = bind_private(path)?;
+        let task = tokio::spawn(accept(listener, event_tx));
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            task,
+        })
+    }
+}
+
+impl Drop for ControlServer {
+    fn drop(&mut self) {
+        self.task.abort();
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Binds `path` so that only the user running the app can ever connect
+///
+/// The socket is created in a fresh `0700` directory next to `path` and moved
+/// into place once its own mode is `0600`, so there is no moment in which it
+/// is open to others.
+fn bind_private(path: &Path) -> io::Result<UnixListener> {
+    let parent = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let name = path.file_name().ok_or(io::ErrorKind::InvalidInput)?;
+    let private = parent.join(format!(
+        ".{}.{}",
+        name.to_string_lossy(),
+        std::process::id()
+    ));
+
+    fs::DirBuilder::new().mode(0o700).create(&private)?;
+    let staged = private.join("sock");
+    let result = UnixListener::bind(&staged).and_then(|listener| {
+        fs::set_permissions(&staged, fs::Permissions::from_mode(0o600))?;
+        fs::rename(&staged, path)?;
+        Ok(listener)
+    });
+    let _ = fs::remove_file(&staged);
+    let _ = fs::remove_dir(&private);
+    result
+}
+
+async fn accept(listener: UnixListener, event_tx: UnboundedSender<Event>) {
+    while let Ok((stream, _)) = listener.accept().await {
+        tokio::spawn(serve(stream, event_tx.clone()));
+    }
+}
+
+/// Answers the requests of one connection until it closes
+async fn serve(stream: UnixStream, event_tx: UnboundedSender<Event>) -> io::Result<()> {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let Some(response) = handle(&line, &event_tx).await else {
+            continue;
+        };
+        let mut response = serde_json::to_vec(&response).map_err(io::Error::other)?;
+        response.push(b'\n');
+        writer.write_all(&response).await?;
+    }
+
+    Ok(())
+}
+
+async fn handle(line: &str, event_tx: &UnboundedSender<Event>) -> Option<Response> {
+    let request = match serde_json::from_str::<Value>(line) {
+        Ok(request) => request,
+        Err(err) => {
+            return Some(Response::new(
+                Value::Null,
+                Err((PARSE_ERROR, err.to_string())),
+            ))
+        }
+    };
+
+    // Invalid requests are answered with their id whenever it can be read
+    let id = request
+        .get("id")
+        .filter(|id| id.is_string() || id.is_number())
+        .cloned()
+        .unwrap_or(Value::Null);
+    let invalid =
+        |message: String| Some(Response::new(id.clone(), Err((INVALID_REQUEST, message))));
+    let request = match serde_json::from_value::<Request>(request) {
+        Ok(request) if request.jsonrpc == "2.0" => request,
+        Ok(_) => return invalid("only JSON-RPC 2.0 is supported".into()),
+        Err(err) => return invalid(err.to_string()),
+    };
+
+    let result = dispatch(request.method, request.params, event_tx).await;
+    Some(Response::new(request.id?, result))
+}
+
+/// Passes the request to the app and waits for its answer
+async fn dispatch(
+    method: String,
+    params: Option<Value>,
+    event_tx: &UnboundedSender<Event>,
+) -> std::result::Result<Value, (i64, String)> {
+    if !ControlCommand::METHODS.contains(&method.as_str()) {
+        return Err((METHOD_NOT_FOUND, format!("unknown method {method:?}")));
+    }
+
+    let mut command = json!({ "method": method });
+    if let Some(params) = params.filter(|params| !params.is_null()) {
+        command["params"] = params;
+    }
+    let command = serde_json::from_value::<ControlCommand>(command)
+        .map_err(|err| (INVALID_PARAMS, err.to_string()))?;
+
+    let gone = || (APP_ERROR, "the app is shutting down".to_string());
+    let (reply, answer) = oneshot::channel();
+    event_tx
+        .send(Event::Control(ControlRequest { command, reply }))
+        .map_err(|_| gone())?;
+    answer
+        .await
+        .map_err(|_| gone())?
+        .map_err(|message| (APP_ERROR, message))
+}
+
+/// Sends one request to the app listening on `path` and returns its result
+pub async fn send(path: &Path, method: &str, params: Option<Value>) -> Result<Value> {
+    let stream = UnixStream::connect(path)
+        .await
+        .with_context(|| format!("connecting to {}", path.display()))?;
+    let (reader, mut writer) = stream.into_split();
+
+    let mut request = json!({ "jsonrpc": "2.0", "id": 1, "method": method });
+    if let Some(params) = params {
+        request["params"] = params;
+    }
+    let mut request = serde_json::to_vec(&request).map_err(io::Error::other)?;
+    request.push(b'\n');
+    writer
+        .write_all(&request)
+        .await
+        .context("sending the request")?;
+
+    let line = BufReader::new(reader)
+        .lines()
+        .next_line()
+        .await
+        .context("reading the response")?
+        .ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof))
+        .context("reading the response")?;
+    let response = serde_json::from_str::<Response>(&line)
+        .map_err(io::Error::other)
+        .context("reading the response")?;
+
+    match response.error {
+        Some(error) => Err(Error::Control(format!(
+            "{} (code {})",
+            error.message, error.code
+        ))),
+        None => Ok(response.result.unwrap_or_default()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::sync::mpsc;
+
+    use super::*;
+
+    async fn error_code(line: &str) -> i64 {
+        let (event_tx, _event_rx) = mpsc::unbounded_channel();
+        let response = handle(line, &event_tx).await.unwrap();
+        assert!(response.result.is_none());
+        response.error.unwrap().code
+    }
+
+    #[tokio::test]
+    async fn rejects_bad_requests() {
+        assert_eq!(error_code("{").await, PARSE_ERROR);
+        assert_eq!(
+            error_code(r#"{"jsonrpc":"1.0","id":1,"method":"quit"}"#).await,
+            INVALID_REQUEST
+        );
+        assert_eq!(
+            error_code(r#"{"jsonrpc":"2.0","id":1,"method":"fly"}"#).await,
+            METHOD_NOT_FOUND
+        );
+        assert_eq!(
+            error_code(r#"{"jsonrpc":"2.0","id":1,"method":"switch_tab","params":{"tab":"Nope"}}"#)
+                .await,
+            INVALID_PARAMS
+        );
+        assert_eq!(
+            error_code(r#"{"jsonrpc":"2.0","id":1,"method":"print"}"#).await,
+            INVALID_PARAMS
+        );
+    }
+
+    #[tokio::test]
+    async fn answers_invalid_requests_with_their_id() {
+        let (event_tx, _event_rx) = mpsc::unbounded_channel();
+        let response = handle(r#"{"jsonrpc":"1.0","id":3,"method":"quit"}"#, &event_tx)
+            .await
+            .unwrap();
+        assert_eq!(response.id, json!(3));
+
+        let response = handle(r#"{"id":"a","method":7}"#, &event_tx).await.unwrap();
+        assert_eq!(response.id, json!("a"));
+
+        let response = handle(r#"{"jsonrpc":"1.0","id":[1],"method":"quit"}"#, &event_tx)
+            .await
+            .unwrap();
+        assert_eq!(response.id, Value::Null);
+    }
+
+    #[tokio::test]
+    async fn passes_requests_to_the_app() {
+        let (event_tx, mut event_rx) = mpsc::unbounded_channel();
+        let app = tokio::spawn(async move {
+            let Some(Event::Control(request)) = event_rx.recv().await else {
+                panic!("expected a control request");
+            };
+            assert!(matches!(request.command, ControlCommand::GetState));
+            request.respond(Ok(json!({ "tab": "One" })));
+        });
+
+        let response = handle(
+            r#"{"jsonrpc":"2.0","id":7,"method":"get_state"}"#,
+            &event_tx,
+        )
+        .await
+        .unwrap();
+        app.await.unwrap();
+        assert_eq!(response.id, json!(7));
+        assert_eq!(response.result, Some(json!({ "tab": "One" })));
+        assert!(response.error.is_none());
+    }
+
+    #[tokio::test]
+    async fn reports_app_errors() {
+        let (event_tx, mut event_rx) = mpsc::unbounded_channel();
+        let app = tokio::spawn(async move {
+            let Some(Event::Control(request)) = event_rx.recv().await else {
+                panic!("expected a control request");
+            };
+            request.respond(Err("no such key".to_string()));
+        });
+
+        let result = dispatch("press".into(), Some(json!({ "key": "Enter" })), &event_tx).await;
+        app.await.unwrap();
+        assert_eq!(result, Err((APP_ERROR, "no such key".to_string())));
+
+        // The event loop has stopped
+        let (event_tx, _) = mpsc::unbounded_channel::<Event>();
+        let result = dispatch("quit".into(), None, &event_tx).await;
+        assert_eq!(result.unwrap_err().0, APP_ERROR);
+    }
+
+    #[tokio::test]
+    async fn answers_notifications_with_nothing() {
+        let (event_tx, mut event_rx) = mpsc::unbounded_channel();
+        let app = tokio::spawn(async move {
+            if let Some(Event::Control(request)) = event_rx.recv().await {
+                request.respond(Ok(Value::Null));
+            }
+        });
+
+        let response = handle(r#"{"jsonrpc":"2.0","method":"refresh"}"#, &event_tx).await;
+        app.await.unwrap();
+        assert!(response.is_none());
+    }
+
+    #[tokio::test]
+    async fn binds_only_for_the_owner() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("client.sock");
+
+        let _listener = bind_private(&path).unwrap();
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        // The staging directory is gone
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+        UnixStream::connect(&path).await.unwrap();
+    }
+}
@@ -1,9 +1,18 @@
 mod app;
+mod backend;
+mod cast;
+pub mod control;
+mod debug_overlay;
 mod error;
+mod record;
+#[cfg(feature = "crossterm")]
+pub mod remote;
 mod screen_manager;
-
-use error::Error;
+mod state;
+mod toast;
 
 pub use app::App;
+pub use error::{Context, Error};
+pub use ratatui::Viewport;
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -1,8 +1,66 @@
+use std::{io, path::Path, process::ExitCode};
+
 use clap::Parser;
-use client::{App, Result};
+use cli::Command;
+use client::{control, App, Context, Result, Viewport};
 
 #[tokio::main]
-async fn main() -> Result<()> {
+async fn main() -> ExitCode {
+    match run().await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{}", err.report());
+            err.exit_code()
+        }
+    }
+}
+
+async fn run() -> Result<()> {
     let args = cli::Cli::parse();
-    App::new(args.frame, args.tick)?.run().await
+    let socket = || {
+        args.socket
+            .clone()
+            .or_else(control::default_socket_path)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no runtime directory"))
+            .context("finding the control socket, pass --socket")
+    };
+
+    if let Some(Command::Send { method, params }) = &args.command {
+        return send(&socket()?, method, params.as_deref()).await;
+    }
+
+    let viewport = args.inline.map_or(Viewport::Fullscreen, Viewport::Inline);
+    let mut app = App::new(args.frame, args.tick, viewport).context("starting the app")?;
+
+    if let Some(path) = &args.record {
+        app.record_to(path)?;
+    }
+    if let Some(path) = &args.replay {
+        app.replay_from(path, args.speed)?;
+    }
+    if let Some(path) = &args.cast {
+        app.cast_to(path)?;
+    }
+    if args.control {
+        app.serve_control(&socket()?)?;
+    }
+
+    app.run().await
+}
+
+#[cfg(unix)]
+async fn send(socket: &Path, method: &str, params: Option<&str>) -> Result<()> {
+    let params = params
+        .map(serde_json::from_str)
+        .transpose()
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))
+        .context("parsing the params")?;
+    let result = control::send(socket, method, params).await?;
+    println!("{result:#}");
+    Ok(())
+}
+
+#[cfg(not(unix))]
+async fn send(_socket: &Path, _method: &str, _params: Option<&str>) -> Result<()> {
+    Err(io::Error::from(io::ErrorKind::Unsupported)).context("sending a control request")
 }
@@ -0,0 +1,238 @@
+//! Recording the input of a session and replaying it later
+//!
+//! A recording is one JSON object per line with the time since the start
+//! and the event, e.g. `{"at_ms":1520,"event":{"Key":{"Char":"q"}}}`.
+//! Only input is kept, ticks and frames follow from the timing on replay.
+
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::Path,
+    time::Duration,
+};
+
+use ratatui::layout::Rect;
+use serde::{Deserialize, Serialize};
+use tokio::{sync::mpsc::UnboundedSender, time::Instant};
+
+use crate::{
+    app::{Event, Input},
+    backend::KeyCode,
+};
+
+#[derive(Debug, Serialize, Deserialize)]
+enum RecordedEvent {
+    Key(KeyCode),
+    Resize { width: u16, height: u16 },
+    Paste(String),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Entry {
+    at_ms: u64,
+    event: RecordedEvent,
+}
+
+/// Appends input events to a recording as they happen
+pub struct Recorder {
+    file: BufWriter<File>,
+    started: Instant,
+}
+
+impl Recorder {
+    pub fn create(path: &Path) -> io::Result<Self> {
+        Ok(Self {
+            file: BufWriter::new(File::create(path)?),
+            started: Instant::now(),
+        })
+    }
+
+    /// Writes `event` if it is input, flushing so a crash keeps everything
+    /// up to it
+    pub fn record(&mut self, event: &Event) -> io::Result<()> {
+        let event = match event {
+            Event::Key(key) => RecordedEvent::Key(*key),
+            Event::Resize(area) => RecordedEvent::Resize {
+                width: area.width,
+                height: area.height,
+            },
+            Event::Paste(text) => RecordedEvent::Paste(text.clone()),
+            _ => return Ok(()),
+        };
+        let entry = Entry {
+            at_ms: self.started.elapsed().as_millis() as u64,
+            event,
+        };
+
+        serde_json::to_writer(&mut self.file, &entry).map_err(io::Error::other)?;
+        self.file.write_all(b"\n")?;
+        self.file.flush()
+    }
+}
+
+/// Reads a recording and sends its input at the original times divided by
+/// `speed`, from a background task that drops `tx` once done
+pub fn replay<T>(path: &Path, speed: f64, tx: UnboundedSender<T>) -> io::Result<()>
+where
+    T: From<Input> + Send + 'static,
+{
+    if !(speed.is_finite() && speed > 0.0) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("replay speed must be above 0, got {speed}"),
+        ));
+    }
+
+    let entries = fs::read_to_string(path)?
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            serde_json::from_str::<Entry>(line).map_err(|err| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("line {}: {err}", index + 1),
+                )
+            })
+        })
+        .collect::<io::Result<Vec<_>>>()?;
+
+    let started = Instant::now();
+    tokio::spawn(async move {
+        for entry in entries {
+            let at = Duration::from_millis(entry.at_ms).div_f64(speed);
+            tokio::time::sleep_until(started + at).await;
+
+            let input = match entry.event {
+                RecordedEvent::Key(key) => Input::Key(key),
+                RecordedEvent::Resize { width, height } => {
+                    Input::Resize(Rect::new(0, 0, width, height))
+                }
+                RecordedEvent::Paste(text) => Input::Paste(text),
+            };
+            if tx.send(input.into()).is_err() {
+                break;
+            }
+        }
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::buffer::Buffer;
+
+    use super::*;
+    use crate::App;
+
+    fn write_recording(path: &Path, events: &[Event]) {
+        let mut recorder = Recorder::create(path).unwrap();
+        for event in events {
+            recorder.record(event).unwrap();
+        }
+    }
+
+    fn contents(buffer: &Buffer) -> String {
+        let area = buffer.area;
+        (area.top()..area.bottom())
+            .map(|y| {
+                (area.left()..area.right())
+                    .map(|x| buffer[(x, y)].symbol())
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    async fn replay_headless(events: &[Event]) -> String {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.jsonl");
+        write_recording(&path, events);
+
+        let mut app = App::headless(60.0, 10.0, 60, 10, &path, 100.0).unwrap();
+        app.run_headless().await.unwrap();
+        contents(app.backend().buffer())
+    }
+
+    #[test]
+    fn records_only_input() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.jsonl");
+        write_recording(
+            &path,
+            &[
+                Event::Key(KeyCode::Char('a')),
+                Event::Tick,
+                Event::Render,
+                Event::Resize(Rect::new(0, 0, 100, 30)),
+                Event::Paste("text".to_string()),
+                Event::Print("line".to_string()),
+            ],
+        );
+
+        let events: Vec<String> = fs::read_to_string(&path)
+            .unwrap()
+            .lines()
+            .map(|line| {
+                let entry: serde_json::Value = serde_json::from_str(line).unwrap();
+                entry["event"].to_string()
+            })
+            .collect();
+        assert_eq!(
+            events,
+            [
+                r#"{"Key":{"Char":"a"}}"#,
+                r#"{"Resize":{"height":30,"width":100}}"#,
+                r#"{"Paste":"text"}"#,
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn replays_keys() {
+        let screen = replay_headless(&[Event::Key(KeyCode::Tab)]).await;
+        assert!(screen.starts_with("tab two"), "{screen}");
+    }
+
+    #[tokio::test]
+    async fn replays_resizes_and_pastes() {
+        let screen = replay_headless(&[
+            Event::Resize(Rect::new(0, 0, 70, 12)),
+            Event::Paste("hello".to_string()),
+        ])
+        .await;
+        assert!(screen.starts_with("tab one"), "{screen}");
+        assert!(screen.contains("pasted into tab one: hello"), "{screen}");
+    }
+
+    #[tokio::test]
+    async fn stops_replaying_on_quit() {
+        let screen = replay_headless(&[
+            Event::Key(KeyCode::Char('2')),
+            Event::Key(KeyCode::Char('q')),
+            Event::Key(KeyCode::Char('1')),
+        ])
+        .await;
+        assert!(screen.starts_with("tab two"), "{screen}");
+    }
+
+    #[test]
+    fn rejects_bad_recordings() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.jsonl");
+        fs::write(
+            &path,
+            "{\"at_ms\":0,\"event\":{\"Key\":\"Enter\"}}\nnot json\n",
+        )
+        .unwrap();
+
+        let (tx, _rx) = tokio::sync::mpsc::unbounded_channel::<Event>();
+        let err = replay(&path, 1.0, tx.clone()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().starts_with("line 2:"), "{err}");
+
+        let err = replay(&path, 0.0, tx).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+}
@@ -0,0 +1,117 @@
+//! Running an [`App`](crate::App) for a terminal at the other end of a
+//! connection, such as an SSH channel or a WebSocket
+//!
+//! The app draws with crossterm escape sequences into a [`RemoteWriter`],
+//! which hands each flushed frame to the connection, and reads keys parsed
+//! from the bytes the remote terminal sends. The size comes from the
+//! connection too, as there is no local tty to ask.
+
+use std::io::{self, Write};
+
+use ratatui::{layout::Rect, prelude::CrosstermBackend, Terminal, TerminalOptions, Viewport};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+
+use crate::app::Input;
+
+mod keys;
+
+pub type RemoteBackend = CrosstermBackend<RemoteWriter>;
+
+/// Terminal output collected until the next flush, then sent as one chunk
+pub struct RemoteWriter {
+    buffer: Vec<u8>,
+    output: UnboundedSender<Vec<u8>>,
+}
+
+impl Write for RemoteWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        self.output
+            .send(std::mem::take(&mut self.buffer))
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "remote terminal is gone"))
+    }
+}
+
+/// Connection side of a remote session
+pub struct RemoteTerminal {
+    pub input: RemoteInput,
+    /// Chunks of output to send, closed once the app is dropped
+    pub output: UnboundedReceiver<Vec<u8>>,
+}
+
+/// Passes what the remote terminal sends to the app
+///
+/// Dropping every clone ends the session's
+/// [`App::run_remote`](crate::App::run_remote).
+#[derive(Clone)]
+pub struct RemoteInput {
+    tx: UnboundedSender<Input>,
+}
+
+impl RemoteInput {
+    /// Passes on bytes typed in the remote terminal
+    pub fn keys(&self, data: &[u8]) {
+        for key in keys::parse(data) {
+            // The app is gone once the session ended, `output` tells the connection
+            let _ = self.tx.send(Input::Key(key));
+        }
+    }
+
+    /// Passes on a new size of the remote terminal
+    pub fn resize(&self, width: u16, height: u16) {
+        let _ = self.tx.send(Input::Resize(Rect::new(0, 0, width, height)));
+    }
+}
+
+/// Creates the terminal for a remote session of `width` x `height` and the
+/// two ends connecting it to the remote terminal
+pub(crate) fn session(
+    width: u16,
+    height: u16,
+) -> io::Result<(
+    Terminal<RemoteBackend>,
+    UnboundedReceiver<Input>,
+    RemoteTerminal,
+)> {
+    let (input_tx, input_rx) = unbounded_channel();
+    let (output_tx, output_rx) = unbounded_channel();
+    let writer = RemoteWriter {
+        buffer: Vec::new(),
+        output: output_tx,
+    };
+    let terminal = Terminal::with_options(
+        CrosstermBackend::new(writer),
+        TerminalOptions {
+            viewport: Viewport::Fixed(Rect::new(0, 0, width, height)),
+        },
+    )?;
+    let remote = RemoteTerminal {
+        input: RemoteInput { tx: input_tx },
+        output: output_rx,
+    };
+    Ok((terminal, input_rx, remote))
+}
+
+/// The remote client sets up raw mode on its side, only the screen is switched
+pub(crate) fn startup(terminal: &mut Terminal<RemoteBackend>) -> io::Result<()> {
+    crossterm::execute!(
+        terminal.backend_mut(),
+        crossterm::terminal::EnterAlternateScreen,
+        crossterm::cursor::Hide
+    )
+}
+
+pub(crate) fn shutdown(terminal: &mut Terminal<RemoteBackend>) -> io::Result<()> {
+    crossterm::execute!(
+        terminal.backend_mut(),
+        crossterm::terminal::LeaveAlternateScreen,
+        crossterm::cursor::Show
+    )
+}
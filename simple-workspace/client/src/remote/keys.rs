@@ -0,0 +1,193 @@
+use crate::backend::KeyCode;
+
+const ESC: u8 = 0x1b;
+
+/// Parses the bytes a terminal sends for key presses
+///
+/// Escape sequences are expected to arrive whole, which holds for what
+/// terminals send per key press. Unknown sequences and control characters
+/// are skipped.
+pub fn parse(data: &[u8]) -> Vec<KeyCode> {
+    let mut keys = Vec::new();
+    let mut rest = data;
+
+    while let Some(&byte) = rest.first() {
+        let (key, len) = match byte {
+            ESC => parse_escape(rest),
+            b'\r' | b'\n' => (Some(KeyCode::Enter), 1),
+            b'\t' => (Some(KeyCode::Tab), 1),
+            0x7f | 0x08 => (Some(KeyCode::Backspace), 1),
+            0x00..=0x1f => (None, 1),
+            _ => parse_char(rest),
+        };
+        keys.extend(key);
+        rest = &rest[len..];
+    }
+
+    keys
+}
+
+fn parse_char(data: &[u8]) -> (Option<KeyCode>, usize) {
+    let len = match data[0] {
+        0xf0..=0xf7 => 4,
+        0xe0..=0xef => 3,
+        0xc0..=0xdf => 2,
+        _ => 1,
+    }
+    .min(data.len());
+
+    let key = std::str::from_utf8(&data[..len])
+        .ok()
+        .and_then(|s| s.chars().next())
+        .map(KeyCode::Char);
+    (key, len)
+}
+
+/// Parses a sequence starting with ESC, a lone ESC is the escape key
+///
+/// ESC before a character is how terminals send it with Alt, which is
+/// dropped like the other modifiers.
+fn parse_escape(data: &[u8]) -> (Option<KeyCode>, usize) {
+    match data.get(1) {
+        Some(b'[') => parse_csi(data),
+        Some(b'O') => {
+            let key = match data.get(2) {
+                Some(b'P') => KeyCode::F(1),
+                Some(b'Q') => KeyCode::F(2),
+                Some(b'R') => KeyCode::F(3),
+                Some(b'S') => KeyCode::F(4),
+                Some(b'H') => KeyCode::Home,
+                Some(b'F') => KeyCode::End,
+                Some(b'A') => KeyCode::Up,
+                Some(b'B') => KeyCode::Down,
+                Some(b'C') => KeyCode::Right,
+                Some(b'D') => KeyCode::Left,
+                _ => return (None, data.len().min(3)),
+            };
+            (Some(key), 3)
+        }
+        Some(0x20..=0x7e | 0x80..) => {
+            let (key, len) = parse_char(&data[1..]);
+            (key, len + 1)
+        }
+        _ => (Some(KeyCode::Esc), 1),
+    }
+}
+
+/// Parses `ESC [ <params> <final byte>`
+fn parse_csi(data: &[u8]) -> (Option<KeyCode>, usize) {
+    let Some(end) = data[2..].iter().position(|b| (0x40..=0x7e).contains(b)) else {
+        return (None, data.len());
+    };
+    let end = end + 2;
+    let params = std::str::from_utf8(&data[2..end]).unwrap_or_default();
+    // Modifiers after `;` are dropped, like the local backends do
+    let number = params.split(';').next().unwrap_or_default();
+
+    let key = match (data[end], number) {
+        (b'A', _) => Some(KeyCode::Up),
+        (b'B', _) => Some(KeyCode::Down),
+        (b'C', _) => Some(KeyCode::Right),
+        (b'D', _) => Some(KeyCode::Left),
+        (b'H', _) => Some(KeyCode::Home),
+        (b'F', _) => Some(KeyCode::End),
+        (b'Z', _) => Some(KeyCode::BackTab),
+        (b'~', "1" | "7") => Some(KeyCode::Home),
+        (b'~', "2") => Some(KeyCode::Insert),
+        (b'~', "3") => Some(KeyCode::Delete),
+        (b'~', "4" | "8") => Some(KeyCode::End),
+        (b'~', "5") => Some(KeyCode::PageUp),
+        (b'~', "6") => Some(KeyCode::PageDown),
+        (b'~', "15") => Some(KeyCode::F(5)),
+        (b'~', "17") => Some(KeyCode::F(6)),
+        (b'~', "18") => Some(KeyCode::F(7)),
+        (b'~', "19") => Some(KeyCode::F(8)),
+        (b'~', "20") => Some(KeyCode::F(9)),
+        (b'~', "21") => Some(KeyCode::F(10)),
+        (b'~', "23") => Some(KeyCode::F(11)),
+        (b'~', "24") => Some(KeyCode::F(12)),
+        _ => None,
+    };
+    (key, end + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(cases: &[(&[u8], &[KeyCode])]) {
+        for (data, keys) in cases {
+            assert_eq!(parse(data), *keys, "parsing {data:?}");
+        }
+    }
+
+    #[test]
+    fn characters() {
+        check(&[
+            (b"a", &[KeyCode::Char('a')]),
+            (b"ab", &[KeyCode::Char('a'), KeyCode::Char('b')]),
+            ("é€".as_bytes(), &[KeyCode::Char('é'), KeyCode::Char('€')]),
+            (b"\r", &[KeyCode::Enter]),
+            (b"\t", &[KeyCode::Tab]),
+            (b"\x7f", &[KeyCode::Backspace]),
+            (b"\x01", &[]),
+            (b"\x1b", &[KeyCode::Esc]),
+        ]);
+    }
+
+    #[test]
+    fn arrows() {
+        check(&[
+            (b"\x1b[A", &[KeyCode::Up]),
+            (b"\x1b[B", &[KeyCode::Down]),
+            (b"\x1b[C", &[KeyCode::Right]),
+            (b"\x1b[D", &[KeyCode::Left]),
+            // Application cursor mode
+            (b"\x1bOA", &[KeyCode::Up]),
+            (b"\x1bOD", &[KeyCode::Left]),
+            // With modifiers, which are dropped
+            (b"\x1b[1;5C", &[KeyCode::Right]),
+            (b"\x1b[A\x1b[B", &[KeyCode::Up, KeyCode::Down]),
+        ]);
+    }
+
+    #[test]
+    fn function_keys() {
+        check(&[
+            (b"\x1bOP", &[KeyCode::F(1)]),
+            (b"\x1bOS", &[KeyCode::F(4)]),
+            (b"\x1b[15~", &[KeyCode::F(5)]),
+            (b"\x1b[17~", &[KeyCode::F(6)]),
+            (b"\x1b[21~", &[KeyCode::F(10)]),
+            (b"\x1b[24~", &[KeyCode::F(12)]),
+            (b"\x1b[24;2~", &[KeyCode::F(12)]),
+            (b"\x1b[3~", &[KeyCode::Delete]),
+            (b"\x1b[5~", &[KeyCode::PageUp]),
+            (b"\x1b[Z", &[KeyCode::BackTab]),
+        ]);
+    }
+
+    #[test]
+    fn alt_prefixed() {
+        check(&[
+            (b"\x1ba", &[KeyCode::Char('a')]),
+            (b"\x1bQ", &[KeyCode::Char('Q')]),
+            ("\x1bé".as_bytes(), &[KeyCode::Char('é')]),
+            (b"\x1ba\x1bb", &[KeyCode::Char('a'), KeyCode::Char('b')]),
+            (b"\x1b\r", &[KeyCode::Esc, KeyCode::Enter]),
+        ]);
+    }
+
+    #[test]
+    fn partial_sequences() {
+        check(&[
+            (b"\x1b[", &[]),
+            (b"\x1b[1;5", &[]),
+            (b"\x1bO", &[]),
+            (b"\xc3", &[]),
+            // Unknown sequences are skipped with what follows them kept
+            (b"\x1b[99~a", &[KeyCode::Char('a')]),
+            (b"\x1bOXa", &[KeyCode::Char('a')]),
+        ]);
+    }
+}
@@ -1,10 +1,11 @@
-use crossterm::event::KeyCode;
 use ratatui::{layout::Rect, Frame};
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc::UnboundedSender;
 
 mod tabone;
 mod tabtwo;
 
-use crate::Result;
+use crate::{app::Event, backend::KeyCode, Context, Result};
 use tabone::TabOne;
 use tabtwo::TabTwo;
 
@@ -12,10 +13,12 @@ use tabtwo::TabTwo;
 pub enum Command {
     Quit,
     SwitchTab(Tab),
+    /// Print a line above the inline viewport
+    Print(String),
     None,
 }
 
-#[derive(Debug, Default, PartialEq)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum Tab {
     #[default]
     One,
@@ -25,15 +28,17 @@ pub enum Tab {
 #[derive(Debug)]
 pub struct ScreenManager {
     current_tab: Tab,
+    event_tx: UnboundedSender<Event>,
 
     tab_one: TabOne,
     tab_two: TabTwo,
 }
 
 impl ScreenManager {
-    pub fn new() -> Self {
+    pub fn new(event_tx: UnboundedSender<Event>) -> Self {
         Self {
             current_tab: Tab::default(),
+            event_tx,
             tab_one: TabOne {},
             tab_two: TabTwo {},
         }
@@ -54,17 +59,54 @@ impl ScreenManager {
                 self.current_tab = Tab::Two;
                 Ok(None)
             }
-            key => match self.current_tab {
-                Tab::One => {
-                    self.tab_one.handle_key(key)?;
-                    Ok(None)
-                }
-                Tab::Two => {
-                    self.tab_two.handle_key(key)?;
-                    Ok(None)
-                }
-            },
+            key => {
+                let command = match self.current_tab {
+                    Tab::One => self
+                        .tab_one
+                        .handle_key(key)
+                        .with_context(|| format!("tab one: handling {key:?}"))?,
+                    Tab::Two => self
+                        .tab_two
+                        .handle_key(key)
+                        .with_context(|| format!("tab two: handling {key:?}"))?,
+                };
+                self.run_command(command)
+            }
+        }
+    }
+
+    /// Passes pasted text to the current tab
+    pub fn handle_paste(&mut self, text: &str) -> Result<()> {
+        let command = match self.current_tab {
+            Tab::One => self
+                .tab_one
+                .handle_paste(text)
+                .context("tab one: handling a paste")?,
+            Tab::Two => self
+                .tab_two
+                .handle_paste(text)
+                .context("tab two: handling a paste")?,
+        };
+        self.run_command(command)?;
+        Ok(())
+    }
+
+    fn run_command(&mut self, command: Command) -> Result<Option<bool>> {
+        match command {
+            Command::Quit => return Ok(Some(true)),
+            Command::SwitchTab(tab) => self.current_tab = tab,
+            Command::Print(line) => self.event_tx.send(Event::Print(line))?,
+            Command::None => {}
         }
+        Ok(None)
+    }
+
+    pub fn current_tab(&self) -> Tab {
+        self.current_tab
+    }
+
+    pub fn set_current_tab(&mut self, tab: Tab) {
+        self.current_tab = tab;
     }
 
     fn toggle_tab(&mut self) {
@@ -81,6 +123,10 @@ pub trait Renderable {
 
 pub trait Eventful {
     fn handle_key(&mut self, key: &KeyCode) -> Result<Command>;
+
+    fn handle_paste(&mut self, _text: &str) -> Result<Command> {
+        Ok(Command::None)
+    }
 }
 
 impl Renderable for ScreenManager {
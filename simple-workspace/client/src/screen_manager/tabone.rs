@@ -1,6 +1,7 @@
-use crossterm::event::KeyCode;
 use ratatui::{layout::Rect, widgets::Paragraph, Frame};
 
+use crate::backend::KeyCode;
+
 use super::{Command, Eventful, Renderable, Tab};
 
 #[derive(Debug)]
@@ -19,7 +20,12 @@ impl Eventful for TabOne {
         match key {
             KeyCode::Char('q') => Ok(Command::Quit),
             KeyCode::Tab => Ok(Command::SwitchTab(Tab::Two)),
+            KeyCode::Enter => Ok(Command::Print("hello from tab one".to_string())),
             _ => Ok(Command::None),
         }
     }
+
+    fn handle_paste(&mut self, text: &str) -> crate::Result<Command> {
+        Ok(Command::Print(format!("pasted into tab one: {text}")))
+    }
 }
@@ -1,6 +1,7 @@
-use crossterm::event::KeyCode;
 use ratatui::{layout::Rect, widgets::Paragraph, Frame};
 
+use crate::backend::KeyCode;
+
 use super::{Command, Eventful, Renderable, Tab};
 
 #[derive(Debug)]
@@ -0,0 +1,150 @@
+//! UI state kept between runs in the XDG state directory
+//!
+//! The file carries a version. Files from older versions are migrated
+//! where possible, anything unreadable or from a newer build is ignored in
+//! favour of the defaults rather than failing to start. New fields need a
+//! `#[serde(default)]`, only changes to existing ones need a new version.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::screen_manager::Tab;
+
+/// Bump when the meaning or type of a field changes, and migrate in [`load`]
+const STATE_VERSION: u64 = 1;
+const STATE_FILE: &str = "state.json";
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UiState {
+    pub tab: Tab,
+    pub debug_overlay: bool,
+}
+
+#[derive(Serialize)]
+struct StateFile<'a> {
+    version: u64,
+    state: &'a UiState,
+}
+
+/// Where the state is kept, e.g. `~/.local/state/client/state.json`
+pub fn state_path() -> PathBuf {
+    dirs::state_dir()
+        .or_else(dirs::data_local_dir)
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(env!("CARGO_PKG_NAME"))
+        .join(STATE_FILE)
+}
+
+/// Reads the saved state, falling back to defaults if there is none usable
+pub fn load(path: &Path) -> UiState {
+    let Ok(contents) = fs::read_to_string(path) else {
+        return UiState::default();
+    };
+    let Ok(file) = serde_json::from_str::<Value>(&contents) else {
+        return UiState::default();
+    };
+
+    match file.get("version").and_then(Value::as_u64) {
+        Some(STATE_VERSION) => file
+            .get("state")
+            .cloned()
+            .and_then(|state| serde_json::from_value(state).ok())
+            .unwrap_or_default(),
+        // No older versions to migrate yet, newer ones are unknown
+        _ => UiState::default(),
+    }
+}
+
+/// Writes the state, replacing the old file only once the new one is complete
+pub fn save(path: &Path, state: &UiState) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let file = StateFile {
+        version: STATE_VERSION,
+        state,
+    };
+    let contents = serde_json::to_string_pretty(&file).map_err(io::Error::other)?;
+
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, contents)?;
+    fs::rename(tmp, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn load_str(contents: &str) -> UiState {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(STATE_FILE);
+        fs::write(&path, contents).unwrap();
+        load(&path)
+    }
+
+    #[test]
+    fn round_trips() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join(STATE_FILE);
+        let state = UiState {
+            tab: Tab::Two,
+            debug_overlay: true,
+        };
+        save(&path, &state).unwrap();
+
+        let loaded = load(&path);
+        assert_eq!(loaded.tab, Tab::Two);
+        assert!(loaded.debug_overlay);
+    }
+
+    #[test]
+    fn missing_fields_use_defaults() {
+        let state = load_str(r#"{"version":1,"state":{"debug_overlay":true}}"#);
+        assert_eq!(state.tab, Tab::default());
+        assert!(state.debug_overlay);
+    }
+
+    #[test]
+    fn other_versions_use_defaults() {
+        for version in [json!(0), json!(2), json!("1"), json!(null)] {
+            let state = load_str(
+                &json!({"version": version, "state": {"tab": "Two", "debug_overlay": true}})
+                    .to_string(),
+            );
+            assert_eq!(state.tab, Tab::default(), "version {version}");
+            assert!(!state.debug_overlay, "version {version}");
+        }
+    }
+
+    #[test]
+    fn corrupt_files_use_defaults() {
+        for contents in [
+            "",
+            "{",
+            "not json",
+            r#"{"state":{"tab":"Two"}}"#,
+            r#"{"version":1,"state":{"tab":"Three"}}"#,
+            r#"{"version":1,"state":[]}"#,
+        ] {
+            let state = load_str(contents);
+            assert_eq!(state.tab, Tab::default(), "loading {contents:?}");
+            assert!(!state.debug_overlay, "loading {contents:?}");
+        }
+    }
+
+    #[test]
+    fn missing_file_uses_defaults() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = load(&dir.path().join(STATE_FILE));
+        assert_eq!(state.tab, Tab::default());
+    }
+}
@@ -0,0 +1,46 @@
+use std::time::{Duration, Instant};
+
+use ratatui::{
+    layout::Rect,
+    widgets::{Block, Clear, Paragraph, Wrap},
+    Frame,
+};
+
+/// How long a line stays on screen
+const TOAST_DURATION: Duration = Duration::from_secs(3);
+/// Widest the toast gets, narrower terminals use their whole width
+const TOAST_WIDTH: u16 = 50;
+
+/// Line printed while drawing on the alternate screen, which has no
+/// scrollback, shown for a moment in the bottom right corner instead
+#[derive(Default)]
+pub struct Toast {
+    line: Option<(String, Instant)>,
+}
+
+impl Toast {
+    /// Shows `line`, replacing the one shown before
+    pub fn show(&mut self, line: String) {
+        self.line = Some((line, Instant::now()));
+    }
+
+    pub fn draw(&mut self, area: Rect, frame: &mut Frame<'_>) {
+        let Some((line, shown)) = &self.line else {
+            return;
+        };
+        if shown.elapsed() >= TOAST_DURATION {
+            self.line = None;
+            return;
+        }
+
+        let width = TOAST_WIDTH.min(area.width);
+        let paragraph = Paragraph::new(line.as_str()).wrap(Wrap { trim: false });
+        // Wrapped inside the borders
+        let lines = paragraph.line_count(width.saturating_sub(2));
+        let height = (lines as u16).saturating_add(2).min(area.height);
+        let area = Rect::new(area.right() - width, area.bottom() - height, width, height);
+
+        frame.render_widget(Clear, area);
+        frame.render_widget(paragraph.block(Block::bordered().title("Print")), area);
+    }
+}
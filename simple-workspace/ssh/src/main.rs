@@ -0,0 +1,290 @@
+use std::{
+    collections::HashMap,
+    io,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    process::ExitCode,
+    sync::Arc,
+    time::Duration,
+};
+
+use clap::Parser;
+use client::{remote::RemoteInput, App, Context};
+use russh::{
+    keys::{
+        load_secret_key,
+        ssh_key::{AuthorizedKeys, PublicKey},
+        Algorithm, PrivateKey,
+    },
+    server::{self, Auth, Msg, Server as _, Session},
+    Channel, ChannelId, CryptoVec, Pty,
+};
+use thiserror::Error;
+use tokio::sync::oneshot;
+use tracing::{error, info};
+
+/// Size used when a client runs the app without requesting a pty
+const DEFAULT_SIZE: (u16, u16) = (80, 24);
+
+#[derive(Debug, Error)]
+enum Error {
+    #[error(transparent)]
+    Ssh(#[from] russh::Error),
+    #[error(transparent)]
+    Client(#[from] client::Error),
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    match run().await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{}", err.report());
+            err.exit_code()
+        }
+    }
+}
+
+async fn run() -> client::Result<()> {
+    let args = cli::SshCli::parse();
+    // Sessions run for as long as the server, their errors go to the log
+    tracing_subscriber::fmt().with_writer(io::stderr).init();
+
+    let host_key = match &args.host_key {
+        Some(path) => load_secret_key(path, None)
+            .map_err(io::Error::other)
+            .context("loading the host key")?,
+        None => PrivateKey::random(&mut rand_core::OsRng, Algorithm::Ed25519)
+            .map_err(io::Error::other)
+            .context("generating a host key")?,
+    };
+
+    // Anonymous users need no keys, unless some are given to let them in too
+    let authorized_keys = match (&args.authorized_keys, args.allow_anonymous) {
+        (None, true) => Vec::new(),
+        (path, _) => {
+            let path = path.clone().unwrap_or_else(default_authorized_keys);
+            authorized_keys(&path)
+                .with_context(|| format!("reading authorized keys from {}", path.display()))?
+        }
+    };
+
+    let config = server::Config {
+        keys: vec![host_key],
+        inactivity_timeout: Some(Duration::from_secs(3600)),
+        auth_rejection_time: Duration::from_secs(1),
+        auth_rejection_time_initial: Some(Duration::ZERO),
+        ..Default::default()
+    };
+
+    let mut server = Server {
+        frame_rate: args.frame,
+        tick_rate: args.tick,
+        authorized_keys: Arc::new(authorized_keys),
+        allow_anonymous: args.allow_anonymous,
+    };
+    info!(listen = %args.listen, "serving SSH");
+    server
+        .run_on_address(Arc::new(config), args.listen)
+        .await
+        .with_context(|| format!("serving SSH on {}", args.listen))
+}
+
+/// `~/.ssh/authorized_keys` of the user running the server
+fn default_authorized_keys() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_default()
+        .join(".ssh")
+        .join("authorized_keys")
+}
+
+fn authorized_keys(path: &Path) -> io::Result<Vec<PublicKey>> {
+    let entries = AuthorizedKeys::read_file(path).map_err(io::Error::other)?;
+    Ok(entries
+        .into_iter()
+        .map(|entry| entry.public_key().clone())
+        .collect())
+}
+
+#[derive(Clone)]
+struct Server {
+    frame_rate: f64,
+    tick_rate: f64,
+    authorized_keys: Arc<Vec<PublicKey>>,
+    allow_anonymous: bool,
+}
+
+impl server::Server for Server {
+    type Handler = Connection;
+
+    fn new_client(&mut self, peer: Option<SocketAddr>) -> Connection {
+        Connection {
+            peer,
+            frame_rate: self.frame_rate,
+            tick_rate: self.tick_rate,
+            authorized_keys: Arc::clone(&self.authorized_keys),
+            allow_anonymous: self.allow_anonymous,
+            sizes: HashMap::new(),
+            inputs: HashMap::new(),
+        }
+    }
+}
+
+/// One SSH connection, running an app for each session channel that asks
+/// for a shell
+struct Connection {
+    peer: Option<SocketAddr>,
+    frame_rate: f64,
+    tick_rate: f64,
+    authorized_keys: Arc<Vec<PublicKey>>,
+    allow_anonymous: bool,
+    /// Terminal sizes from pty requests, until the shell starts
+    sizes: HashMap<ChannelId, (u16, u16)>,
+    inputs: HashMap<ChannelId, RemoteInput>,
+}
+
+impl server::Handler for Connection {
+    type Error = Error;
+
+    // Users get the app, never a shell, whatever name they log in with
+    async fn auth_none(&mut self, _user: &str) -> Result<Auth, Self::Error> {
+        if self.allow_anonymous {
+            Ok(Auth::Accept)
+        } else {
+            Ok(reject())
+        }
+    }
+
+    async fn auth_publickey(
+        &mut self,
+        _user: &str,
+        public_key: &PublicKey,
+    ) -> Result<Auth, Self::Error> {
+        // Comments in authorized_keys don't have to match the client's
+        let authorized = self
+            .authorized_keys
+            .iter()
+            .any(|key| key.key_data() == public_key.key_data());
+        if authorized {
+            Ok(Auth::Accept)
+        } else {
+            Ok(reject())
+        }
+    }
+
+    async fn channel_open_session(
+        &mut self,
+        _channel: Channel<Msg>,
+        _session: &mut Session,
+    ) -> Result<bool, Self::Error> {
+        Ok(true)
+    }
+
+    async fn pty_request(
+        &mut self,
+        channel: ChannelId,
+        _term: &str,
+        col_width: u32,
+        row_height: u32,
+        _pix_width: u32,
+        _pix_height: u32,
+        _modes: &[(Pty, u32)],
+        session: &mut Session,
+    ) -> Result<(), Self::Error> {
+        self.sizes
+            .insert(channel, (clamp(col_width), clamp(row_height)));
+        session.channel_success(channel)?;
+        Ok(())
+    }
+
+    async fn shell_request(
+        &mut self,
+        channel: ChannelId,
+        session: &mut Session,
+    ) -> Result<(), Self::Error> {
+        let (width, height) = self.sizes.remove(&channel).unwrap_or(DEFAULT_SIZE);
+        let (mut app, mut remote) = App::remote(self.frame_rate, self.tick_rate, width, height)?;
+        self.inputs.insert(channel, remote.input);
+
+        let peer = self.peer;
+        info!(?peer, ?channel, width, height, "starting a session");
+        let (exit_tx, exit_rx) = oneshot::channel();
+        tokio::spawn(async move {
+            let status = match app.run_remote().await {
+                Ok(()) => 0,
+                Err(err) => {
+                    error!(?peer, ?channel, "session failed: {}", err.report());
+                    1
+                }
+            };
+            let _ = exit_tx.send(status);
+        });
+
+        // Sends the frames until the app is dropped, then ends the session
+        // with its exit status, which clients otherwise report as unknown
+        let handle = session.handle();
+        tokio::spawn(async move {
+            while let Some(output) = remote.output.recv().await {
+                if handle.data(channel, CryptoVec::from(output)).await.is_err() {
+                    break;
+                }
+            }
+            let status = exit_rx.await.unwrap_or(1);
+            let _ = handle.exit_status_request(channel, status).await;
+            let _ = handle.eof(channel).await;
+            let _ = handle.close(channel).await;
+        });
+
+        session.channel_success(channel)?;
+        Ok(())
+    }
+
+    async fn data(
+        &mut self,
+        channel: ChannelId,
+        data: &[u8],
+        _session: &mut Session,
+    ) -> Result<(), Self::Error> {
+        if let Some(input) = self.inputs.get(&channel) {
+            input.keys(data);
+        }
+        Ok(())
+    }
+
+    async fn window_change_request(
+        &mut self,
+        channel: ChannelId,
+        col_width: u32,
+        row_height: u32,
+        _pix_width: u32,
+        _pix_height: u32,
+        _session: &mut Session,
+    ) -> Result<(), Self::Error> {
+        if let Some(input) = self.inputs.get(&channel) {
+            input.resize(clamp(col_width), clamp(row_height));
+        }
+        Ok(())
+    }
+
+    async fn channel_close(
+        &mut self,
+        channel: ChannelId,
+        _session: &mut Session,
+    ) -> Result<(), Self::Error> {
+        // Dropping the input ends the app
+        self.inputs.remove(&channel);
+        self.sizes.remove(&channel);
+        Ok(())
+    }
+}
+
+fn reject() -> Auth {
+    Auth::Reject {
+        proceed_with_methods: None,
+        partial_success: false,
+    }
+}
+
+fn clamp(size: u32) -> u16 {
+    size.try_into().unwrap_or(u16::MAX)
+}
@@ -0,0 +1,261 @@
+use std::{io, net::SocketAddr, process::ExitCode};
+
+use axum::{
+    extract::{
+        ws::{Message, WebSocket},
+        State, WebSocketUpgrade,
+    },
+    http::{
+        header::{CONTENT_TYPE, HOST, ORIGIN},
+        HeaderMap, StatusCode,
+    },
+    response::{Html, IntoResponse, Response},
+    routing::get,
+    Router,
+};
+use clap::Parser;
+use client::{remote::RemoteTerminal, App, Context, Result};
+use futures::{SinkExt, StreamExt};
+use serde::Deserialize;
+
+/// Page running xterm.js, connecting back to `/ws`
+const INDEX_HTML: &str = include_str!("index.html");
+/// xterm.js 5.5.0 and its fit addon 0.10.0, served by us so the page works
+/// offline and runs no third-party code
+const XTERM_CSS: &str = include_str!("xterm.css");
+const XTERM_JS: &str = include_str!("xterm.js");
+const ADDON_FIT_JS: &str = include_str!("addon-fit.js");
+
+/// Messages from the page
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum ClientMessage {
+    /// Typed input as xterm.js encodes it for a terminal
+    Data {
+        data: String,
+    },
+    Resize {
+        cols: u16,
+        rows: u16,
+    },
+}
+
+#[derive(Clone, Copy)]
+struct Rates {
+    frame_rate: f64,
+    tick_rate: f64,
+}
+
+#[derive(Clone, Copy)]
+struct Server {
+    /// Address we listen on, the only one the page may be loaded from
+    listen: SocketAddr,
+    rates: Rates,
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    match run().await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{}", err.report());
+            err.exit_code()
+        }
+    }
+}
+
+async fn run() -> Result<()> {
+    let args = cli::WebCli::parse();
+    let server = Server {
+        listen: args.listen,
+        rates: Rates {
+            frame_rate: args.frame,
+            tick_rate: args.tick,
+        },
+    };
+
+    let router = Router::new()
+        .route("/", get(|| async { Html(INDEX_HTML) }))
+        .route(
+            "/xterm.css",
+            get(|| async { ([(CONTENT_TYPE, "text/css")], XTERM_CSS) }),
+        )
+        .route(
+            "/xterm.js",
+            get(|| async { ([(CONTENT_TYPE, "text/javascript")], XTERM_JS) }),
+        )
+        .route(
+            "/addon-fit.js",
+            get(|| async { ([(CONTENT_TYPE, "text/javascript")], ADDON_FIT_JS) }),
+        )
+        .route("/ws", get(upgrade))
+        .with_state(server);
+
+    let listener = tokio::net::TcpListener::bind(args.listen)
+        .await
+        .with_context(|| format!("listening on {}", args.listen))?;
+    println!("serving on http://{}", args.listen);
+    axum::serve(listener, router)
+        .await
+        .context("serving the page")
+}
+
+async fn upgrade(
+    ws: WebSocketUpgrade,
+    headers: HeaderMap,
+    State(server): State<Server>,
+) -> Response {
+    // Browsers let any site open a WebSocket here, only our own page may
+    if !same_origin(&headers, server.listen) {
+        return (
+            StatusCode::FORBIDDEN,
+            "cross-origin requests are not allowed",
+        )
+            .into_response();
+    }
+
+    ws.on_upgrade(move |socket| async move {
+        if let Err(err) = session(socket, server.rates).await {
+            eprintln!("{}", err.report());
+        }
+    })
+}
+
+/// Whether the request comes from a page served by this server, which the
+/// browser tells with the `Origin` header
+///
+/// A DNS-rebinding page gets its own name into both `Origin` and `Host`, so
+/// `Host` must also be one of the names we are reachable under locally.
+fn same_origin(headers: &HeaderMap, listen: SocketAddr) -> bool {
+    let header = |name| headers.get(name).and_then(|value| value.to_str().ok());
+    let (Some(origin), Some(host)) = (header(ORIGIN), header(HOST)) else {
+        return false;
+    };
+    if !local_host(host, listen) {
+        return false;
+    }
+    origin
+        .strip_prefix("http://")
+        .or_else(|| origin.strip_prefix("https://"))
+        .is_some_and(|authority| authority.eq_ignore_ascii_case(host))
+}
+
+/// Whether `host` names the address we listen on
+fn local_host(host: &str, listen: SocketAddr) -> bool {
+    let port = listen.port();
+    [
+        listen.to_string(),
+        format!("localhost:{port}"),
+        format!("127.0.0.1:{port}"),
+    ]
+    .iter()
+    .any(|allowed| allowed.eq_ignore_ascii_case(host))
+}
+
+/// Runs one app for the lifetime of a WebSocket
+async fn session(socket: WebSocket, rates: Rates) -> Result<()> {
+    let (mut sink, mut stream) = socket.split();
+
+    // The page sends its size first, the app can't start without it
+    let (width, height) = loop {
+        match next_message(&mut stream).await? {
+            Some(ClientMessage::Resize { cols, rows }) => break (cols, rows),
+            Some(ClientMessage::Data { .. }) => continue,
+            None => return Ok(()),
+        }
+    };
+
+    let (mut app, remote) = App::remote(rates.frame_rate, rates.tick_rate, width, height)?;
+    let RemoteTerminal { input, mut output } = remote;
+    let app = tokio::spawn(async move { app.run_remote().await });
+
+    loop {
+        tokio::select! {
+            message = next_message(&mut stream) => match message? {
+                Some(ClientMessage::Data { data }) => input.keys(data.as_bytes()),
+                Some(ClientMessage::Resize { cols, rows }) => input.resize(cols, rows),
+                None => break,
+            },
+            chunk = output.recv() => match chunk {
+                Some(chunk) => sink
+                    .send(Message::Binary(chunk))
+                    .await
+                    .map_err(io::Error::other)
+                    .context("sending to the browser")?,
+                // The app quit
+                None => break,
+            },
+        }
+    }
+
+    // Ends the app if the page went away first
+    drop(input);
+    let _ = sink.close().await;
+    app.await
+        .map_err(io::Error::other)
+        .context("running the app")?
+}
+
+/// Next message from the page, `None` once the socket closed
+async fn next_message<S>(stream: &mut S) -> Result<Option<ClientMessage>>
+where
+    S: StreamExt<Item = std::result::Result<Message, axum::Error>> + Unpin,
+{
+    while let Some(message) = stream.next().await {
+        let text = match message.map_err(io::Error::other)? {
+            Message::Text(text) => text,
+            Message::Close(_) => return Ok(None),
+            _ => continue,
+        };
+        let message = serde_json::from_str(&text)
+            .map_err(io::Error::other)
+            .context("reading a message from the browser")?;
+        return Ok(Some(message));
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::HeaderValue;
+
+    use super::*;
+
+    const LISTEN: SocketAddr = SocketAddr::V4(std::net::SocketAddrV4::new(
+        std::net::Ipv4Addr::new(127, 0, 0, 1),
+        8080,
+    ));
+
+    fn allowed(origin: Option<&'static str>, host: &'static str) -> bool {
+        let mut headers = HeaderMap::new();
+        headers.insert(HOST, HeaderValue::from_static(host));
+        if let Some(origin) = origin {
+            headers.insert(ORIGIN, HeaderValue::from_static(origin));
+        }
+        same_origin(&headers, LISTEN)
+    }
+
+    #[test]
+    fn accepts_the_own_page() {
+        assert!(allowed(Some("http://127.0.0.1:8080"), "127.0.0.1:8080"));
+        assert!(allowed(Some("http://LocalHost:8080"), "localhost:8080"));
+    }
+
+    #[test]
+    fn rejects_other_origins() {
+        assert!(!allowed(Some("https://evil.example"), "127.0.0.1:8080"));
+        assert!(!allowed(Some("http://127.0.0.1:9090"), "127.0.0.1:8080"));
+        assert!(!allowed(Some("null"), "127.0.0.1:8080"));
+        assert!(!allowed(None, "127.0.0.1:8080"));
+    }
+
+    #[test]
+    fn rejects_other_hosts() {
+        // DNS rebinding: the attacker's name in both headers
+        assert!(!allowed(
+            Some("http://evil.example:8080"),
+            "evil.example:8080"
+        ));
+        assert!(!allowed(Some("http://localhost:9090"), "localhost:9090"));
+    }
+}
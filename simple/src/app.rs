@@ -1,41 +1,57 @@
-use ratatui::{
-    crossterm::{
-        self,
-        event::{self, Event, KeyCode, KeyEventKind},
-    },
-    layout::Rect,
-    widgets::Paragraph,
-    DefaultTerminal, Frame,
-};
+use ratatui::{layout::Rect, widgets::Paragraph, Frame, Terminal};
 
-use crate::Result;
+use crate::{
+    backend::{self, Backend, EventSource, KeyCode},
+    error::{Context, Error},
+    Result,
+};
 
 pub struct App {
     should_quit: bool,
+    input: EventSource,
+    terminal: Terminal<Backend>,
 }
 
 impl App {
     pub fn new() -> Result<Self> {
-        Ok(Self { should_quit: false })
+        let terminal = backend::terminal().context("creating the terminal")?;
+        let input = EventSource::new().context("reading terminal input")?;
+
+        Ok(Self {
+            should_quit: false,
+            input,
+            terminal,
+        })
     }
 
-    pub fn run(mut self, mut terminal: DefaultTerminal) -> Result<()> {
-        startup()?;
+    /// Runs the UI until quit, restoring the terminal even if it fails
+    pub fn run(mut self) -> Result<()> {
+        let mut result = backend::startup(&mut self.terminal).context("setting up the terminal");
+
+        if result.is_ok() {
+            result = self.event_loop();
+        }
+
+        result.and(backend::shutdown(&mut self.terminal).context("restoring the terminal"))
+    }
 
+    fn event_loop(&mut self) -> Result<()> {
         while !self.should_quit {
-            terminal.draw(|frame| {
-                self.render(frame.area(), frame);
-            })?;
+            self.terminal
+                .draw(|frame| {
+                    render(frame.area(), frame);
+                })
+                .context("drawing a frame")?;
 
-            if let Event::Key(key) = event::read()? {
-                if key.kind != KeyEventKind::Press {
-                    continue;
-                }
-                self.handle_key_event(&key.code)?;
-            }
+            let key = self
+                .input
+                .next_key()
+                .ok_or(Error::Input)?
+                .context("reading terminal input")?;
+            self.handle_key_event(&key)?;
         }
 
-        shutdown()
+        Ok(())
     }
 
     fn handle_key_event(&mut self, key: &KeyCode) -> Result<()> {
@@ -45,21 +61,9 @@ impl App {
         }
         Ok(())
     }
-
-    fn render(&self, area: Rect, frame: &mut Frame) {
-        let paragraph = Paragraph::new("Hi");
-        frame.render_widget(paragraph, area);
-    }
-}
-
-fn startup() -> Result<()> {
-    crossterm::terminal::enable_raw_mode()?;
-    crossterm::execute!(std::io::stdout(), crossterm::terminal::EnterAlternateScreen)?;
-    Ok(())
 }
 
-fn shutdown() -> Result<()> {
-    crossterm::terminal::disable_raw_mode()?;
-    crossterm::execute!(std::io::stdout(), crossterm::terminal::LeaveAlternateScreen)?;
-    Ok(())
+fn render(area: Rect, frame: &mut Frame) {
+    let paragraph = Paragraph::new("Hi");
+    frame.render_widget(paragraph, area);
 }
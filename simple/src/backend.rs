@@ -0,0 +1,69 @@
+//! Terminal backend picked with the `crossterm` (default), `termion` or
+//! `termwiz` cargo feature
+//!
+//! Each backend module provides the same items: the ratatui `Backend` type,
+//! `terminal` to create it, `startup` and `shutdown` to switch the terminal
+//! in and out of raw mode, and an `EventSource` blocking until the next
+//! [`KeyCode`]. The app only uses these, so it works the same on each.
+//! Only one may be enabled, so pick another one with e.g.
+//! `cargo run --no-default-features --features termion`.
+
+#[cfg(feature = "crossterm")]
+mod crossterm;
+#[cfg(feature = "crossterm")]
+pub use self::crossterm::{shutdown, startup, terminal, Backend, EventSource};
+
+// The other backends step aside for the one before them, so that enabling
+// several only reports the error below
+#[cfg(all(feature = "termion", not(feature = "crossterm")))]
+mod termion;
+#[cfg(all(feature = "termion", not(feature = "crossterm")))]
+pub use self::termion::{shutdown, startup, terminal, Backend, EventSource};
+
+#[cfg(all(
+    feature = "termwiz",
+    not(any(feature = "crossterm", feature = "termion"))
+))]
+mod termwiz;
+#[cfg(all(
+    feature = "termwiz",
+    not(any(feature = "crossterm", feature = "termion"))
+))]
+pub use self::termwiz::{shutdown, startup, terminal, Backend, EventSource};
+
+#[cfg(not(any(feature = "crossterm", feature = "termion", feature = "termwiz")))]
+compile_error!("enable one of the `crossterm`, `termion` or `termwiz` features");
+
+#[cfg(any(
+    all(feature = "crossterm", feature = "termion"),
+    all(feature = "crossterm", feature = "termwiz"),
+    all(feature = "termion", feature = "termwiz"),
+))]
+compile_error!(
+    "enable only one of the `crossterm`, `termion` or `termwiz` features, \
+     crossterm is a default one so add `--no-default-features` to pick another"
+);
+
+/// Key press, independent of the backend
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyCode {
+    Char(char),
+    F(u8),
+    Enter,
+    Esc,
+    Tab,
+    // termwiz reports Shift+Tab as a shifted `Tab`
+    #[cfg_attr(feature = "termwiz", allow(dead_code))]
+    BackTab,
+    Backspace,
+    Delete,
+    Insert,
+    Up,
+    Down,
+    Left,
+    Right,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+}
@@ -0,0 +1,77 @@
+use std::io::{self, Stdout};
+
+use crossterm::event::{self, Event, KeyCode as CrosstermKeyCode, KeyEventKind};
+use ratatui::{prelude::CrosstermBackend, Terminal};
+
+use super::KeyCode;
+
+pub type Backend = CrosstermBackend<Stdout>;
+
+pub fn terminal() -> io::Result<Terminal<Backend>> {
+    Terminal::new(CrosstermBackend::new(io::stdout()))
+}
+
+pub fn startup(terminal: &mut Terminal<Backend>) -> io::Result<()> {
+    crossterm::terminal::enable_raw_mode()?;
+    crossterm::execute!(
+        terminal.backend_mut(),
+        crossterm::terminal::EnterAlternateScreen
+    )
+}
+
+pub fn shutdown(terminal: &mut Terminal<Backend>) -> io::Result<()> {
+    crossterm::terminal::disable_raw_mode()?;
+    crossterm::execute!(
+        terminal.backend_mut(),
+        crossterm::terminal::LeaveAlternateScreen
+    )
+}
+
+pub struct EventSource;
+
+impl EventSource {
+    pub fn new() -> io::Result<Self> {
+        Ok(Self)
+    }
+
+    /// Blocks until the next key press, `None` once input is closed
+    pub fn next_key(&mut self) -> Option<io::Result<KeyCode>> {
+        loop {
+            let event = match event::read() {
+                Ok(event) => event,
+                Err(err) => return Some(Err(err)),
+            };
+            if let Event::Key(key) = event {
+                if key.kind == KeyEventKind::Press {
+                    if let Some(key) = convert_key(key.code) {
+                        return Some(Ok(key));
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn convert_key(key: CrosstermKeyCode) -> Option<KeyCode> {
+    let key = match key {
+        CrosstermKeyCode::Char(c) => KeyCode::Char(c),
+        CrosstermKeyCode::F(n) => KeyCode::F(n),
+        CrosstermKeyCode::Enter => KeyCode::Enter,
+        CrosstermKeyCode::Esc => KeyCode::Esc,
+        CrosstermKeyCode::Tab => KeyCode::Tab,
+        CrosstermKeyCode::BackTab => KeyCode::BackTab,
+        CrosstermKeyCode::Backspace => KeyCode::Backspace,
+        CrosstermKeyCode::Delete => KeyCode::Delete,
+        CrosstermKeyCode::Insert => KeyCode::Insert,
+        CrosstermKeyCode::Up => KeyCode::Up,
+        CrosstermKeyCode::Down => KeyCode::Down,
+        CrosstermKeyCode::Left => KeyCode::Left,
+        CrosstermKeyCode::Right => KeyCode::Right,
+        CrosstermKeyCode::Home => KeyCode::Home,
+        CrosstermKeyCode::End => KeyCode::End,
+        CrosstermKeyCode::PageUp => KeyCode::PageUp,
+        CrosstermKeyCode::PageDown => KeyCode::PageDown,
+        _ => return None,
+    };
+    Some(key)
+}
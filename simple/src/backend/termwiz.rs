@@ -0,0 +1,89 @@
+use std::io;
+
+use ratatui::{prelude::TermwizBackend, Terminal};
+use termwiz::{
+    caps::Capabilities,
+    input::{InputEvent, KeyCode as TermwizKeyCode},
+    surface::{Change, CursorVisibility},
+    terminal::{buffered::BufferedTerminal, SystemTerminal, Terminal as _},
+};
+
+use super::KeyCode;
+
+pub type Backend = TermwizBackend;
+
+pub fn terminal() -> io::Result<Terminal<Backend>> {
+    // `TermwizBackend::new` would enter raw mode and the alternate screen
+    // right away, leave that to `startup`
+    let caps = Capabilities::new_from_env().map_err(io::Error::other)?;
+    let system = SystemTerminal::new_from_stdio(caps).map_err(io::Error::other)?;
+    let buffered = BufferedTerminal::new(system).map_err(io::Error::other)?;
+    Terminal::new(TermwizBackend::with_buffered_terminal(buffered))
+}
+
+pub fn startup(terminal: &mut Terminal<Backend>) -> io::Result<()> {
+    let system = terminal.backend_mut().buffered_terminal_mut().terminal();
+    system.set_raw_mode().map_err(io::Error::other)?;
+    system.enter_alternate_screen().map_err(io::Error::other)
+}
+
+pub fn shutdown(terminal: &mut Terminal<Backend>) -> io::Result<()> {
+    let buffered = terminal.backend_mut().buffered_terminal_mut();
+    buffered.add_change(Change::CursorVisibility(CursorVisibility::Visible));
+    buffered.flush().map_err(io::Error::other)?;
+    let system = buffered.terminal();
+    system.exit_alternate_screen().map_err(io::Error::other)?;
+    system.set_cooked_mode().map_err(io::Error::other)
+}
+
+/// Keys read from a second handle to the terminal, so input doesn't need the
+/// one drawing
+pub struct EventSource {
+    input: SystemTerminal,
+}
+
+impl EventSource {
+    pub fn new() -> io::Result<Self> {
+        let caps = Capabilities::new_from_env().map_err(io::Error::other)?;
+        let input = SystemTerminal::new_from_stdio(caps).map_err(io::Error::other)?;
+        Ok(Self { input })
+    }
+
+    /// Blocks until the next key press, `None` once input is closed
+    pub fn next_key(&mut self) -> Option<io::Result<KeyCode>> {
+        loop {
+            match self.input.poll_input(None) {
+                Ok(Some(InputEvent::Key(key))) => {
+                    if let Some(key) = convert_key(key.key) {
+                        return Some(Ok(key));
+                    }
+                }
+                Ok(_) => {}
+                Err(err) => return Some(Err(io::Error::other(err))),
+            }
+        }
+    }
+}
+
+fn convert_key(key: TermwizKeyCode) -> Option<KeyCode> {
+    let key = match key {
+        TermwizKeyCode::Char(c) => KeyCode::Char(c),
+        TermwizKeyCode::Function(n) => KeyCode::F(n),
+        TermwizKeyCode::Enter => KeyCode::Enter,
+        TermwizKeyCode::Escape => KeyCode::Esc,
+        TermwizKeyCode::Tab => KeyCode::Tab,
+        TermwizKeyCode::Backspace => KeyCode::Backspace,
+        TermwizKeyCode::Delete => KeyCode::Delete,
+        TermwizKeyCode::Insert => KeyCode::Insert,
+        TermwizKeyCode::UpArrow => KeyCode::Up,
+        TermwizKeyCode::DownArrow => KeyCode::Down,
+        TermwizKeyCode::LeftArrow => KeyCode::Left,
+        TermwizKeyCode::RightArrow => KeyCode::Right,
+        TermwizKeyCode::Home => KeyCode::Home,
+        TermwizKeyCode::End => KeyCode::End,
+        TermwizKeyCode::PageUp => KeyCode::PageUp,
+        TermwizKeyCode::PageDown => KeyCode::PageDown,
+        _ => return None,
+    };
+    Some(key)
+}
@@ -1,11 +1,19 @@
+use std::process::ExitCode;
+
 use app::App;
 
 mod app;
+mod backend;
 mod error;
 
 type Result<T> = std::result::Result<T, error::Error>;
 
-fn main() -> Result<()> {
-    let terminal = ratatui::init();
-    App::new()?.run(terminal)
+fn main() -> ExitCode {
+    match App::new().and_then(App::run) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{}", err.report());
+            err.exit_code()
+        }
+    }
 }